@@ -0,0 +1,150 @@
+use derive_setters::Setters;
+use stardust_xr_asteroids::{
+	Context, CreateInnerInfo, CustomElement, FnWrapper, Transformable, ValidState,
+};
+use stardust_xr_fusion::{
+	drawable::{Lines, LinesAspect},
+	fields::{Field, FieldAspect, Shape},
+	input::{InputDataType::Pointer, InputHandler},
+	node::NodeError,
+	root::FrameInfo,
+	spatial::{Spatial, SpatialRef, Transform},
+	values::{color::rgba_linear, Color, Vector2},
+};
+use stardust_xr_molecules::{
+	input_action::{InputQueue, InputQueueable, SimpleAction},
+	lines::{rounded_rectangle, LineExt},
+};
+
+const THICKNESS: f32 = 0.0025;
+
+/// A small flat button next to a panel, poked with a finger or controller tip or clicked with a pointer.
+/// Children (usually a label) are put on its face.
+#[derive_where::derive_where(Debug, PartialEq)]
+#[derive(Setters)]
+#[setters(into, strip_option)]
+pub struct ActionButton<State: ValidState> {
+	pub transform: Transform,
+	pub size: Vector2<f32>,
+	/// lit up in the accent color, e.g. for toggles that are on
+	pub active: bool,
+	#[setters(skip)]
+	pub on_press: FnWrapper<dyn Fn(&mut State) + Send + Sync>,
+}
+impl<State: ValidState> ActionButton<State> {
+	pub fn new(
+		size: impl Into<Vector2<f32>>,
+		on_press: impl Fn(&mut State) + Send + Sync + 'static,
+	) -> Self {
+		ActionButton {
+			transform: Transform::identity(),
+			size: size.into(),
+			active: false,
+			on_press: FnWrapper(Box::new(on_press)),
+		}
+	}
+}
+impl<State: ValidState> CustomElement<State> for ActionButton<State> {
+	type Inner = ActionButtonInner;
+	type Resource = ();
+	type Error = NodeError;
+
+	fn create_inner(
+		&self,
+		context: &Context,
+		info: CreateInnerInfo,
+		_resource: &mut Self::Resource,
+	) -> Result<Self::Inner, Self::Error> {
+		let root = Spatial::create(info.parent_space, self.transform)?;
+		let outline = Lines::create(&root, Transform::identity(), &[])?;
+		let field = Field::create(
+			&root,
+			Transform::identity(),
+			Shape::Box([self.size.x, self.size.y, THICKNESS].into()),
+		)?;
+		let input = InputHandler::create(&root, Transform::none(), &field)?.queue()?;
+		let mut inner = ActionButtonInner {
+			root,
+			outline,
+			field,
+			input,
+			action: SimpleAction::default(),
+			pressed: false,
+			accent_color: context.accent_color.color(),
+		};
+		inner.update_outline(self.size, self.active);
+		Ok(inner)
+	}
+
+	fn diff(&self, old: &Self, inner: &mut Self::Inner, _resource: &mut Self::Resource) {
+		self.apply_transform(old, &inner.root);
+		if self.size != old.size {
+			let _ = inner
+				.field
+				.set_shape(Shape::Box([self.size.x, self.size.y, THICKNESS].into()));
+		}
+		if self.size != old.size || self.active != old.active {
+			inner.update_outline(self.size, self.active);
+		}
+	}
+
+	fn frame(
+		&self,
+		_context: &Context,
+		_info: &FrameInfo,
+		state: &mut State,
+		inner: &mut Self::Inner,
+	) {
+		if inner.update() {
+			(self.on_press.0)(state);
+		}
+	}
+
+	fn spatial_aspect(&self, inner: &Self::Inner) -> SpatialRef {
+		inner.root.clone().as_spatial_ref()
+	}
+}
+impl<State: ValidState> Transformable for ActionButton<State> {
+	fn transform(&self) -> &Transform {
+		&self.transform
+	}
+	fn transform_mut(&mut self) -> &mut Transform {
+		&mut self.transform
+	}
+}
+
+pub struct ActionButtonInner {
+	root: Spatial,
+	outline: Lines,
+	field: Field,
+	input: InputQueue,
+	action: SimpleAction,
+	pressed: bool,
+	accent_color: Color,
+}
+impl ActionButtonInner {
+	fn update_outline(&mut self, size: Vector2<f32>, active: bool) {
+		let color = if active {
+			self.accent_color
+		} else {
+			rgba_linear!(0.5, 0.5, 0.5, 1.0)
+		};
+		let line = rounded_rectangle(size.x, size.y, size.y * 0.25, 4)
+			.thickness(THICKNESS * 0.5)
+			.color(color);
+		let _ = self.outline.set_lines(&[line]);
+	}
+
+	/// Returns true the frame the button gets pressed.
+	fn update(&mut self) -> bool {
+		self.input.handle_events();
+		self.action.update(&self.input, &|data| match &data.input {
+			Pointer(_) => data.datamap.with_data(|d| d.idx("select").as_f32() > 0.5),
+			_ => data.distance < 0.0,
+		});
+		let pressed = !self.action.currently_acting().is_empty();
+		let just_pressed = pressed && !self.pressed;
+		self.pressed = pressed;
+		just_pressed
+	}
+}
@@ -0,0 +1,214 @@
+use crate::panel_pose::{body_frame, PanelPose};
+use serde::{Deserialize, Serialize};
+use stardust_xr_fusion::{
+	node::NodeType,
+	spatial::{Spatial, SpatialAspect, SpatialRef, SpatialRefAspect},
+};
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// How often a panel that isn't world-locked catches up with the user.
+const FOLLOW_STEP: Duration = Duration::from_millis(11);
+/// How far a body-locked panel can fall behind before it starts catching up, in meters.
+const LAZY_DISTANCE: f32 = 0.25;
+/// How far the user can turn away from a body-locked panel before it starts catching up, in radians.
+const LAZY_ANGLE: f32 = 0.5;
+/// How close a body-locked panel has to get to where it should be to stop catching up, in meters.
+const SETTLED_DISTANCE: f32 = 0.005;
+/// How quickly a body-locked panel catches up, per second.
+const CATCH_UP_RATE: f32 = 4.0;
+
+/// What a panel stays put relative to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Anchor {
+	/// the room, wherever it was put
+	#[default]
+	World,
+	/// the user's position and heading, lazily catching up once they've moved or turned away
+	Body,
+	/// the user's head, so it stays in the same spot in view
+	Head,
+}
+impl Anchor {
+	/// The next mode, for toggling through them from the toolbar.
+	pub fn next(self) -> Self {
+		match self {
+			Anchor::World => Anchor::Body,
+			Anchor::Body => Anchor::Head,
+			Anchor::Head => Anchor::World,
+		}
+	}
+	pub fn label(self) -> &'static str {
+		match self {
+			Anchor::World => "world",
+			Anchor::Body => "body",
+			Anchor::Head => "head",
+		}
+	}
+}
+
+/// The pose of what `anchor` follows, relative to `root`.
+async fn anchor_pose(
+	anchor: Anchor,
+	hmd: &SpatialRef,
+	root: &impl SpatialRefAspect,
+) -> Option<PanelPose> {
+	let hmd_transform = hmd.get_transform(root).await.ok()?;
+	match anchor {
+		Anchor::World => None,
+		Anchor::Body => {
+			let (translation, rotation) = body_frame(&hmd_transform);
+			Some(PanelPose::new(translation, rotation))
+		}
+		Anchor::Head => PanelPose::from_transform(&hmd_transform),
+	}
+}
+
+/// Works out where a panel following an anchor goes each step.
+#[derive(Debug)]
+struct Follower {
+	anchor: Anchor,
+	/// where the panel is relative to the anchor
+	offset: Option<PanelPose>,
+	/// the last pose this put the panel at, to notice it being moved by something else
+	last_sent: Option<PanelPose>,
+	catching_up: bool,
+}
+impl Follower {
+	fn new(anchor: Anchor) -> Self {
+		Follower {
+			anchor,
+			offset: None,
+			last_sent: None,
+			catching_up: false,
+		}
+	}
+
+	/// With the anchor at `frame` and the panel at `current`, where to put the panel next if it should move,
+	/// and the panel's new offset from the anchor if it was just put somewhere new.
+	fn step(
+		&mut self,
+		frame: PanelPose,
+		current: PanelPose,
+		grabbing: bool,
+	) -> (Option<PanelPose>, Option<PanelPose>) {
+		// whoever's holding the panel decides where it goes, it stays put relative to the anchor from there
+		if grabbing {
+			self.offset = None;
+			return (None, None);
+		}
+		let mut new_offset = None;
+		if self.offset.is_none() || self.last_sent != Some(current) {
+			let offset = frame.inverse().compose(&current);
+			self.offset = Some(offset);
+			self.last_sent = Some(current);
+			self.catching_up = false;
+			new_offset = Some(offset);
+		}
+		let Some(offset) = self.offset else {
+			return (None, new_offset);
+		};
+		let target = frame.compose(&offset);
+
+		let next = match self.anchor {
+			Anchor::World => return (None, new_offset),
+			Anchor::Head => target,
+			Anchor::Body => {
+				let distance = current.translation().distance(target.translation());
+				let angle = current.rotation().angle_between(target.rotation());
+				self.catching_up |= distance > LAZY_DISTANCE || angle > LAZY_ANGLE;
+				if !self.catching_up {
+					return (None, new_offset);
+				}
+				if distance < SETTLED_DISTANCE {
+					self.catching_up = false;
+				}
+				let t = 1.0 - (-CATCH_UP_RATE * FOLLOW_STEP.as_secs_f32()).exp();
+				current.lerp(&target, t)
+			}
+		};
+		if next == current {
+			return (None, new_offset);
+		}
+		self.last_sent = Some(next);
+		(Some(next), new_offset)
+	}
+}
+
+/// Keep `spatial` where it is relative to what `anchor` follows, until aborted.
+/// Whenever the panel's pose changes from elsewhere (someone moves it, a pose request...)
+/// it stays put relative to the anchor from its new pose instead, and that offset is sent to `offset_tx`.
+pub async fn follow(
+	anchor: Anchor,
+	spatial: Spatial,
+	hmd: watch::Receiver<Option<SpatialRef>>,
+	grabbing: watch::Receiver<bool>,
+	mut pose: watch::Receiver<Option<PanelPose>>,
+	pose_tx: watch::Sender<Option<PanelPose>>,
+	offset_tx: watch::Sender<Option<PanelPose>>,
+) {
+	if anchor == Anchor::World {
+		return;
+	}
+	let client = spatial.client().clone();
+	let root = client.get_root();
+	let mut interval = tokio::time::interval(FOLLOW_STEP);
+	let mut follower = Follower::new(anchor);
+	loop {
+		interval.tick().await;
+		let Some(hmd) = hmd.borrow().clone() else {
+			continue;
+		};
+		let Some(current) = *pose.borrow_and_update() else {
+			continue;
+		};
+		let Some(frame) = anchor_pose(anchor, &hmd, root).await else {
+			continue;
+		};
+		let (next, new_offset) = follower.step(frame, current, *grabbing.borrow());
+		if let Some(offset) = new_offset {
+			let _ = offset_tx.send(Some(offset));
+		}
+		if let Some(next) = next {
+			let _ = spatial.set_relative_transform(root, next.transform());
+			let _ = pose_tx.send(Some(next));
+		}
+	}
+}
+
+#[test]
+fn body_follow_catches_up() {
+	use glam::{vec3, Quat, Vec3};
+	let mut follower = Follower::new(Anchor::Body);
+	let mut panel = PanelPose::new(vec3(0.0, 1.5, -1.0), Quat::IDENTITY);
+	let mut offsets = 0;
+	let mut step = |follower: &mut Follower, panel: &mut PanelPose, frame: PanelPose| {
+		let (next, offset) = follower.step(frame, *panel, false);
+		offsets += offset.is_some() as u32;
+		if let Some(next) = next {
+			*panel = next;
+		}
+	};
+	let start = PanelPose::new(Vec3::ZERO, Quat::IDENTITY);
+	step(&mut follower, &mut panel, start);
+	// small steps stay put
+	step(
+		&mut follower,
+		&mut panel,
+		PanelPose::new(vec3(0.1, 0.0, 0.0), Quat::IDENTITY),
+	);
+	assert_eq!(panel.translation(), vec3(0.0, 1.5, -1.0));
+	// walking away a meter brings the panel along
+	let walked = PanelPose::new(vec3(1.0, 0.0, 0.0), Quat::IDENTITY);
+	for _ in 0..1000 {
+		step(&mut follower, &mut panel, walked);
+	}
+	assert!(panel.translation().distance(vec3(1.0, 1.5, -1.0)) < 0.01);
+	// following never counts as the panel being put somewhere new
+	assert_eq!(offsets, 1);
+
+	// moving it from elsewhere does
+	let moved = PanelPose::new(vec3(1.0, 1.0, -2.0), Quat::IDENTITY);
+	let (_, offset) = follower.step(walked, moved, false);
+	assert_eq!(offset.map(|o| o.translation()), Some(vec3(0.0, 1.0, -2.0)));
+}
@@ -0,0 +1,216 @@
+use glam::{vec2, vec3, Quat, Vec2, Vec3};
+use stardust_xr_asteroids::{
+	ClientState, Context, CreateInnerInfo, CustomElement, Transformable, ValidState,
+};
+use stardust_xr_fusion::{
+	drawable::{Model, ModelPart},
+	fields::Shape,
+	items::panel::{PanelItem, PanelItemAspect, SurfaceId},
+	node::NodeError,
+	root::FrameInfo,
+	spatial::{Spatial, SpatialAspect, SpatialRef, Transform},
+	values::{ResourceID, Vector2},
+};
+use std::f32::consts::PI;
+
+use crate::State;
+
+/// Radius panels get bent to when curving is turned on, roughly how far away they're used from.
+pub const DEFAULT_CURVE_RADIUS: f32 = 1.5;
+/// Panels never wrap further than half way around their cylinder.
+const MAX_ARC: f32 = PI;
+/// Number of flat strips in `panel_curved.glb`.
+const STRIPS: usize = 32;
+
+// A curved panel is wrapped around a vertical cylinder whose axis is `radius` in front
+// of the panel's center, so every column of pixels is the same distance from that axis.
+// "Flat" coordinates are what the panel would be if it was unrolled: x along the surface,
+// y up, z off the front of the surface.
+
+/// The radius a panel this wide can actually be bent to.
+pub fn clamp_radius(radius: f32, width: f32) -> f32 {
+	radius.max(width / MAX_ARC)
+}
+
+/// Flat coordinates to where they end up on the curved panel.
+pub fn bend(point: Vec3, radius: f32) -> Vec3 {
+	let angle = point.x / radius;
+	let distance = radius - point.z;
+	vec3(
+		distance * angle.sin(),
+		point.y,
+		radius - (distance * angle.cos()),
+	)
+}
+
+/// A point near the curved panel to flat coordinates.
+pub fn flatten(point: Vec3, radius: f32) -> Vec3 {
+	let offset = vec2(point.x, radius - point.z);
+	vec3(
+		radius * offset.x.atan2(offset.y),
+		point.y,
+		radius - offset.length(),
+	)
+}
+
+/// Which way the curved surface faces at flat x.
+pub fn surface_rotation(x: f32, radius: f32) -> Quat {
+	Quat::from_rotation_y(-x / radius)
+}
+
+/// Box around the whole curved panel, centered on it like the flat panel's box.
+pub fn bounds(size: Vec2, thickness: f32, radius: f32) -> Vec3 {
+	let half_angle = size.x / radius / 2.0;
+	let sagitta = radius * (1.0 - half_angle.cos());
+	vec3(
+		2.0 * radius * half_angle.sin(),
+		size.y,
+		thickness + (sagitta * 2.0),
+	)
+}
+
+/// Field shape for a panel, flat or curved.
+pub fn field_shape(size: Vec2, thickness: f32, curve_radius: Option<f32>) -> Shape {
+	match curve_radius {
+		Some(radius) => Shape::Box(bounds(size, thickness, radius).into()),
+		None => Shape::Box([size.x, size.y, thickness].into()),
+	}
+}
+
+/// Where a ray hits the curved surface, skipping the far side of the cylinder.
+pub fn ray_hit(origin: Vec3, direction: Vec3, radius: f32) -> Option<Vec3> {
+	let origin_2d = vec2(origin.x, origin.z - radius);
+	let direction_2d = vec2(direction.x, direction.z);
+	let a = direction_2d.length_squared();
+	let b = 2.0 * origin_2d.dot(direction_2d);
+	let c = origin_2d.length_squared() - (radius * radius);
+	let discriminant = (b * b) - (4.0 * a * c);
+	if a <= f32::EPSILON || discriminant < 0.0 {
+		return None;
+	}
+	let root = discriminant.sqrt();
+	[(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)]
+		.into_iter()
+		.filter(|t| *t >= 0.0)
+		.map(|t| origin + (direction * t))
+		.find(|hit| hit.z < radius)
+}
+
+/// The panel model bent around a cylinder, made of thin flat strips.
+#[derive(Debug, PartialEq)]
+pub struct CurvedPanelModel {
+	pub transform: Transform,
+	pub panel_item: PanelItem,
+	pub surface_id: SurfaceId,
+	pub size: Vector2<f32>,
+	pub thickness: f32,
+	pub radius: f32,
+}
+impl<S: ValidState> CustomElement<S> for CurvedPanelModel {
+	type Inner = CurvedPanelModelInner;
+	type Resource = ();
+	type Error = NodeError;
+
+	fn create_inner(
+		&self,
+		_context: &Context,
+		info: CreateInnerInfo,
+		_resource: &mut Self::Resource,
+	) -> Result<Self::Inner, Self::Error> {
+		let root = Spatial::create(info.parent_space, self.transform)?;
+		let model = Model::create(
+			&root,
+			Transform::identity(),
+			&ResourceID::new_namespaced(State::APP_ID, "panel_curved"),
+		)?;
+		let strips = (0..STRIPS)
+			.map(|i| model.part(&format!("Strip{i}")))
+			.collect::<Result<Vec<_>, _>>()?;
+		let inner = CurvedPanelModelInner {
+			root,
+			_model: model,
+			strips,
+		};
+		inner.apply_surface(&self.panel_item, self.surface_id);
+		inner.layout(self.size.into(), self.thickness, self.radius);
+		Ok(inner)
+	}
+
+	fn diff(&self, old: &Self, inner: &mut Self::Inner, _resource: &mut Self::Resource) {
+		self.apply_transform(old, &inner.root);
+		if self.panel_item != old.panel_item || self.surface_id != old.surface_id {
+			inner.apply_surface(&self.panel_item, self.surface_id);
+		}
+		if self.size != old.size || self.thickness != old.thickness || self.radius != old.radius {
+			inner.layout(self.size.into(), self.thickness, self.radius);
+		}
+	}
+
+	fn frame(
+		&self,
+		_context: &Context,
+		_info: &FrameInfo,
+		_state: &mut S,
+		_inner: &mut Self::Inner,
+	) {
+	}
+
+	fn spatial_aspect(&self, inner: &Self::Inner) -> SpatialRef {
+		inner.root.clone().as_spatial_ref()
+	}
+}
+impl Transformable for CurvedPanelModel {
+	fn transform(&self) -> &Transform {
+		&self.transform
+	}
+	fn transform_mut(&mut self) -> &mut Transform {
+		&mut self.transform
+	}
+}
+
+pub struct CurvedPanelModelInner {
+	root: Spatial,
+	_model: Model,
+	strips: Vec<ModelPart>,
+}
+impl CurvedPanelModelInner {
+	fn apply_surface(&self, panel_item: &PanelItem, surface_id: SurfaceId) {
+		for strip in &self.strips {
+			let _ = panel_item.apply_surface_material(surface_id, strip);
+		}
+	}
+
+	/// Each strip shows its own slice of the surface, so they only need to be put along the arc.
+	fn layout(&self, size: Vec2, thickness: f32, radius: f32) {
+		let strip_angle = size.x / radius / STRIPS as f32;
+		let strip_width = 2.0 * radius * (strip_angle / 2.0).sin();
+		for (i, strip) in self.strips.iter().enumerate() {
+			let x = ((i as f32 + 0.5) / STRIPS as f32 - 0.5) * size.x;
+			// the strips are chords of the arc, a little closer to the axis than its middle
+			let center = bend(vec3(x, 0.0, 0.0), radius)
+				+ (surface_rotation(x, radius)
+					* Vec3::Z * radius
+					* (1.0 - (strip_angle / 2.0).cos()));
+			let _ = strip.set_local_transform(Transform::from_translation_rotation_scale(
+				center,
+				surface_rotation(x, radius),
+				[strip_width, size.y, thickness],
+			));
+		}
+	}
+}
+
+#[test]
+fn flatten_undoes_bend() {
+	let point = vec3(0.8, 0.3, 0.02);
+	let bent = bend(point, 1.2);
+	assert!(flatten(bent, 1.2).distance(point) < 0.0001);
+}
+
+#[test]
+fn pointer_ray_hits_curved_edge() {
+	// from the cylinder's axis, every point on the panel is one radius away
+	let hit = ray_hit(vec3(0.0, 0.0, 1.5), vec3(1.0, 0.0, -1.0), 1.5).unwrap();
+	assert!((vec3(hit.x, 0.0, hit.z - 1.5).length() - 1.5).abs() < 0.0001);
+	assert!((flatten(hit, 1.5).x - (1.5 * PI / 4.0)).abs() < 0.0001);
+}
@@ -0,0 +1,110 @@
+use crate::{
+	anchoring::{follow, Anchor},
+	panel_pose::{body_frame, PanelPose},
+};
+use stardust_xr_asteroids::{Context, CreateInnerInfo, CustomElement, ValidState};
+use stardust_xr_fusion::{
+	node::{NodeError, NodeType},
+	objects::hmd,
+	spatial::{Spatial, SpatialAspect, SpatialRef, SpatialRefAspect, Transform},
+};
+use stardust_xr_molecules::dbus::AbortOnDrop;
+use tokio::sync::watch;
+
+/// A spot for Flatland's own controls, put relative to the user's body when it's created
+/// and whenever `pose` changes, then following them like a body-anchored panel does.
+/// Children are attached to it.
+#[derive(Debug, PartialEq)]
+pub struct Dock {
+	/// relative to the user's position and heading, like `PoseAnchor::Body`
+	pub pose: PanelPose,
+}
+impl Dock {
+	fn place(&self, inner: &DockInner) {
+		let spatial = inner.spatial.clone();
+		let mut hmd = inner.hmd.clone();
+		let pose_tx = inner.pose_tx.clone();
+		let pose = self.pose;
+		tokio::task::spawn(async move {
+			let Some(hmd) = hmd
+				.wait_for(Option::is_some)
+				.await
+				.ok()
+				.and_then(|hmd| hmd.clone())
+			else {
+				return;
+			};
+			let root = spatial.client().get_root().clone();
+			let Ok(hmd_transform) = hmd.get_transform(&root).await else {
+				return;
+			};
+			let (body_translation, body_rotation) = body_frame(&hmd_transform);
+			// the same frame following uses, so it keeps `pose` as the offset from the body
+			let placed = PanelPose::new(body_translation, body_rotation).compose(&pose);
+			let _ = spatial.set_relative_transform(&root, placed.transform());
+			// not where following put it, so following picks up from here with the new offset
+			let _ = pose_tx.send(Some(placed));
+		});
+	}
+}
+
+pub struct DockInner {
+	spatial: Spatial,
+	hmd: watch::Receiver<Option<SpatialRef>>,
+	pose_tx: watch::Sender<Option<PanelPose>>,
+	_follow_task: AbortOnDrop,
+}
+impl<State: ValidState> CustomElement<State> for Dock {
+	type Inner = DockInner;
+	type Resource = ();
+	type Error = NodeError;
+
+	fn create_inner(
+		&self,
+		_context: &Context,
+		info: CreateInnerInfo,
+		_resource: &mut Self::Resource,
+	) -> Result<Self::Inner, Self::Error> {
+		let spatial = Spatial::create(info.parent_space, Transform::identity())?;
+		let (hmd_tx, hmd_rx) = watch::channel(None);
+		tokio::task::spawn({
+			let client = spatial.client().clone();
+			async move {
+				if let Some(hmd) = hmd(&client).await {
+					let _ = hmd_tx.send(Some(hmd));
+				}
+			}
+		});
+		let (pose_tx, pose) = watch::channel(None);
+		// nobody grabs the dock, and its offset from the body is always `pose`
+		// so the one following works out isn't kept anywhere
+		let grabbing = watch::Sender::new(false).subscribe();
+		let follow_task = tokio::task::spawn(follow(
+			Anchor::Body,
+			spatial.clone(),
+			hmd_rx.clone(),
+			grabbing,
+			pose,
+			pose_tx.clone(),
+			watch::Sender::new(None),
+		));
+		let inner = DockInner {
+			spatial,
+			hmd: hmd_rx,
+			pose_tx,
+			_follow_task: AbortOnDrop(follow_task.abort_handle()),
+		};
+		self.place(&inner);
+		Ok(inner)
+	}
+
+	fn diff(&self, old: &Self, inner: &mut Self::Inner, _resource: &mut Self::Resource) {
+		if self.pose != old.pose {
+			self.place(inner);
+		}
+	}
+
+	fn spatial_aspect(&self, inner: &Self::Inner) -> SpatialRef {
+		inner.spatial.clone().as_spatial_ref()
+	}
+}
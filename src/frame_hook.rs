@@ -0,0 +1,48 @@
+use stardust_xr_asteroids::{Context, CreateInnerInfo, CustomElement, FnWrapper, ValidState};
+use stardust_xr_fusion::{
+	node::NodeError,
+	root::FrameInfo,
+	spatial::{Spatial, SpatialRef, Transform},
+};
+
+/// Calls back into the state every frame, for bookkeeping that spans more than one substate.
+#[derive_where::derive_where(Debug, PartialEq)]
+#[allow(clippy::type_complexity)]
+pub struct FrameHook<State: ValidState>(
+	pub FnWrapper<dyn Fn(&mut State, &FrameInfo) + Send + Sync>,
+);
+impl<State: ValidState> FrameHook<State> {
+	pub fn new(f: impl Fn(&mut State, &FrameInfo) + Send + Sync + 'static) -> Self {
+		FrameHook(FnWrapper(Box::new(f)))
+	}
+}
+impl<State: ValidState> CustomElement<State> for FrameHook<State> {
+	type Inner = Spatial;
+	type Resource = ();
+	type Error = NodeError;
+
+	fn create_inner(
+		&self,
+		_context: &Context,
+		info: CreateInnerInfo,
+		_resource: &mut Self::Resource,
+	) -> Result<Self::Inner, Self::Error> {
+		Spatial::create(info.parent_space, Transform::identity())
+	}
+
+	fn diff(&self, _old_self: &Self, _inner: &mut Self::Inner, _resource: &mut Self::Resource) {}
+
+	fn frame(
+		&self,
+		_context: &Context,
+		info: &FrameInfo,
+		state: &mut State,
+		_inner: &mut Self::Inner,
+	) {
+		(self.0 .0)(state, info);
+	}
+
+	fn spatial_aspect(&self, inner: &Self::Inner) -> SpatialRef {
+		inner.clone().as_spatial_ref()
+	}
+}
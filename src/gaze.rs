@@ -0,0 +1,110 @@
+use crate::panel_pose::PanelPose;
+use glam::{Vec2, Vec3, Vec3Swizzles};
+use stardust_xr_asteroids::{Context, CreateInnerInfo, CustomElement, FnWrapper, ValidState};
+use stardust_xr_fusion::{
+	node::{NodeError, NodeType},
+	objects::hmd,
+	root::FrameInfo,
+	spatial::{Spatial, SpatialRef, SpatialRefAspect, Transform},
+};
+use stardust_xr_molecules::dbus::AbortOnDrop;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// How often the user's head is looked up.
+const GAZE_STEP: Duration = Duration::from_millis(30);
+
+/// Calls back with the user's head pose whenever it's been looked up, for focus-follows-gaze and tiling.
+#[derive_where::derive_where(Debug, PartialEq)]
+#[allow(clippy::type_complexity)]
+pub struct GazeTracker<State: ValidState>(
+	pub FnWrapper<dyn Fn(&mut State, PanelPose) + Send + Sync>,
+);
+impl<State: ValidState> GazeTracker<State> {
+	pub fn new(f: impl Fn(&mut State, PanelPose) + Send + Sync + 'static) -> Self {
+		GazeTracker(FnWrapper(Box::new(f)))
+	}
+}
+
+pub struct GazeTrackerInner {
+	spatial: Spatial,
+	head: watch::Receiver<Option<PanelPose>>,
+	_task: AbortOnDrop,
+}
+impl<State: ValidState> CustomElement<State> for GazeTracker<State> {
+	type Inner = GazeTrackerInner;
+	type Resource = ();
+	type Error = NodeError;
+
+	fn create_inner(
+		&self,
+		_context: &Context,
+		info: CreateInnerInfo,
+		_resource: &mut Self::Resource,
+	) -> Result<Self::Inner, Self::Error> {
+		let spatial = Spatial::create(info.parent_space, Transform::identity())?;
+		let (head_tx, head) = watch::channel(None);
+		let client = spatial.client().clone();
+		let task = tokio::task::spawn(async move {
+			let Some(hmd) = hmd(&client).await else {
+				return;
+			};
+			let mut interval = tokio::time::interval(GAZE_STEP);
+			loop {
+				interval.tick().await;
+				if let Ok(transform) = hmd.get_transform(client.get_root()).await {
+					let _ = head_tx.send(PanelPose::from_transform(&transform));
+				}
+			}
+		});
+		Ok(GazeTrackerInner {
+			spatial,
+			head,
+			_task: AbortOnDrop(task.abort_handle()),
+		})
+	}
+
+	fn diff(&self, _old_self: &Self, _inner: &mut Self::Inner, _resource: &mut Self::Resource) {}
+
+	fn frame(
+		&self,
+		_context: &Context,
+		_info: &FrameInfo,
+		state: &mut State,
+		inner: &mut Self::Inner,
+	) {
+		if inner.head.has_changed().is_ok_and(|t| t) {
+			if let Some(head) = *inner.head.borrow_and_update() {
+				(self.0 .0)(state, head);
+			}
+		}
+	}
+
+	fn spatial_aspect(&self, inner: &Self::Inner) -> SpatialRef {
+		inner.spatial.clone().as_spatial_ref()
+	}
+}
+
+/// How far along the user's line of sight from `head` it hits a flat panel of `size` at `pose`, if it does.
+pub fn gaze_distance(head: PanelPose, pose: PanelPose, size: Vec2) -> Option<f32> {
+	let local = pose.inverse();
+	let origin = local.compose(&head).translation();
+	let direction = local.rotation() * (head.rotation() * Vec3::NEG_Z);
+	if direction.z.abs() <= f32::EPSILON {
+		return None;
+	}
+	let distance = -origin.z / direction.z;
+	let hit = origin + direction * distance;
+	(distance > 0.0 && hit.xy().abs().cmple(size / 2.0).all()).then_some(distance)
+}
+
+#[test]
+fn gaze_hits_panel_in_front() {
+	use glam::{vec2, vec3, Quat};
+	let head = PanelPose::new(vec3(0.0, 1.6, 0.0), Quat::from_rotation_y(0.2));
+	let pose = PanelPose::new(vec3(-0.2, 1.6, -1.0), Quat::IDENTITY);
+	let distance = gaze_distance(head, pose, vec2(0.6, 0.4)).unwrap();
+	assert!((distance - 1.0 / 0.2_f32.cos()).abs() < 0.0001);
+	let beside = PanelPose::new(vec3(0.3, 1.6, -1.0), Quat::IDENTITY);
+	assert!(gaze_distance(head, beside, vec2(0.6, 0.4)).is_none());
+}
@@ -1,3 +1,4 @@
+use crate::pointer_input::ScrollData;
 use glam::Vec3;
 use stardust_xr_fusion::{
 	drawable::{Lines, LinesAspect},
@@ -49,6 +50,8 @@ pub struct GrabBall<H: GrabBallHead> {
 	input: InputQueue,
 	grab_action: SingleAction,
 	pos: Vec3,
+	/// for pointers, how far along the ray the ball is held
+	pointer_distance: f32,
 }
 impl<H: GrabBallHead> GrabBall<H> {
 	pub fn create(
@@ -83,6 +86,7 @@ impl<H: GrabBallHead> GrabBall<H> {
 			input: input_handler,
 			grab_action,
 			pos: offset,
+			pointer_distance: 0.0,
 		})
 	}
 
@@ -91,7 +95,7 @@ impl<H: GrabBallHead> GrabBall<H> {
 			true,
 			&self.input,
 			|input| match &input.input {
-				InputDataType::Pointer(_) => false,
+				InputDataType::Pointer(_) => true,
 				_ => input.distance < (self.settings.radius + self.settings.padding),
 			},
 			|input| {
@@ -102,6 +106,16 @@ impl<H: GrabBallHead> GrabBall<H> {
 			},
 		);
 
+		if let Some(grabbing) = self.grab_action.actor() {
+			if let InputDataType::Pointer(p) = &grabbing.input {
+				if self.grab_action.actor_started() {
+					self.pointer_distance =
+						Vec3::from(p.origin).distance(Vec3::from(p.deepest_point));
+				}
+				// push and pull along the ray with scroll
+				self.pointer_distance += ScrollData::of(grabbing).push_pull();
+			}
+		}
 		if self.grab_action.actor_stopped() {
 			self.pos = self.offset;
 			let _ = self.head.root().set_relative_transform(
@@ -135,7 +149,10 @@ impl<H: GrabBallHead> GrabBall<H> {
 	fn grab_point(&self) -> Option<Vec3> {
 		let grabbing = self.grab_action.actor()?;
 		match &grabbing.input {
-			InputDataType::Pointer(_) => None,
+			InputDataType::Pointer(p) => Some(
+				Vec3::from(p.origin)
+					+ Vec3::from(p.direction()).normalize() * self.pointer_distance,
+			),
 			InputDataType::Hand(h) => {
 				Some(Vec3::from(h.thumb.tip.position).lerp(Vec3::from(h.index.tip.position), 0.5))
 			}
@@ -0,0 +1,270 @@
+use glam::{Vec2, Vec3};
+use serde::{Deserialize, Serialize};
+use stardust_xr_fusion::input::Hand;
+
+/// How close the thumb and index fingertips have to be to pinch, in meters.
+const PINCH_DISTANCE: f32 = 0.02;
+/// How close the thumb and index fingertips have to be for precision mode, short of pinching, in meters.
+const HALF_PINCH_DISTANCE: f32 = 0.04;
+/// How close the index and middle fingertips have to be for a two finger swipe, in meters.
+const SWIPE_FINGER_GAP: f32 = 0.025;
+/// How far the thumb has to stay from both fingertips for a two finger swipe, in meters.
+const SWIPE_THUMB_GAP: f32 = 0.05;
+/// How far a pinch has to move before it scrolls instead of clicking, in meters.
+const DRAG_THRESHOLD: f32 = 0.015;
+/// How long a pinch has to stay put before it holds the left button down to drag-select, in seconds.
+const HOLD_TIME: f32 = 0.25;
+/// How far two fingers together have to move before they scroll, in meters.
+const SWIPE_THRESHOLD: f32 = 0.01;
+/// How much of the latest movement goes into the scroll speed each frame.
+const VELOCITY_SMOOTHING: f32 = 0.3;
+/// How quickly scrolling slows down after letting go, per second.
+const INERTIA_FRICTION: f32 = 4.0;
+/// Scroll speed under which scrolling after letting go stops, in meters per second.
+const INERTIA_MIN_SPEED: f32 = 0.02;
+
+/// How hands click and scroll on panels.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HandGestureSettings {
+	/// pinch and drag to scroll, so a pinch only clicks once let go of and has to be held still
+	/// for a moment to drag, otherwise a pinch presses the left button right away
+	pub pinch_scroll: bool,
+}
+
+/// The point between the thumb and index fingertips, if they're pinching.
+pub fn pinch(hand: &Hand) -> Option<Vec3> {
+	let thumb = Vec3::from(hand.thumb.tip.position);
+	let index = Vec3::from(hand.index.tip.position);
+	(thumb.distance(index) < PINCH_DISTANCE).then(|| thumb.lerp(index, 0.5))
+}
+
+/// Whether the thumb and index fingertips are close but not pinching yet.
+pub fn half_pinch(hand: &Hand) -> bool {
+	let thumb = Vec3::from(hand.thumb.tip.position);
+	let index = Vec3::from(hand.index.tip.position);
+	(PINCH_DISTANCE..HALF_PINCH_DISTANCE).contains(&thumb.distance(index))
+}
+
+/// The point between the index and middle fingertips, if they're held together with the thumb out of the way.
+pub fn two_fingers(hand: &Hand) -> Option<Vec3> {
+	let thumb = Vec3::from(hand.thumb.tip.position);
+	let index = Vec3::from(hand.index.tip.position);
+	let middle = Vec3::from(hand.middle.tip.position);
+	(index.distance(middle) < SWIPE_FINGER_GAP
+		&& thumb.distance(index).min(thumb.distance(middle)) > SWIPE_THUMB_GAP)
+		.then(|| index.lerp(middle, 0.5))
+}
+
+/// What a hand over a panel is doing this frame, with positions on the panel in meters.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct HandInput {
+	pub pinch: Option<Vec2>,
+	pub two_fingers: Option<Vec2>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Click {
+	Press,
+	Release,
+	/// pressed and released at once, for a quick pinch
+	Tap,
+}
+
+/// What to do about a hand this frame.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct HandGesture {
+	pub left_click: Option<Click>,
+	/// how far the hand dragged the content, in meters, +y up
+	pub scroll: Vec2,
+	/// the cursor should stay put, since the hand might still turn out to be clicking or is scrolling
+	pub holding_cursor: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Pinch {
+	/// not yet moved far enough to scroll or held long enough to drag
+	Undecided {
+		start: Vec2,
+		held: f32,
+	},
+	/// holding the left button down
+	Dragging,
+	Scrolling {
+		last: Vec2,
+	},
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Swipe {
+	Undecided { start: Vec2 },
+	Scrolling { last: Vec2 },
+}
+
+/// Presses the left button while pinching, or with `pinch_scroll` tells a quick pinch (click),
+/// a pinch held in place (drag-select) and a pinch dragged (scroll) apart.
+/// Two fingers held together swipe to scroll. Scrolling keeps going for a bit after letting go.
+#[derive(Debug, Default)]
+pub struct HandGestures {
+	pinch: Option<Pinch>,
+	swipe: Option<Swipe>,
+	/// in meters per second
+	velocity: Vec2,
+}
+impl HandGestures {
+	/// `delta` is the time since the last frame in seconds.
+	pub fn update(
+		&mut self,
+		settings: &HandGestureSettings,
+		input: HandInput,
+		delta: f32,
+	) -> HandGesture {
+		let mut gesture = HandGesture::default();
+		let mut moved = Vec2::ZERO;
+		self.pinch = match (self.pinch, input.pinch) {
+			(None, Some(_)) if !settings.pinch_scroll => {
+				gesture.left_click = Some(Click::Press);
+				Some(Pinch::Dragging)
+			}
+			(None, Some(pos)) => Some(Pinch::Undecided {
+				start: pos,
+				held: 0.0,
+			}),
+			(Some(Pinch::Undecided { start, .. }), Some(pos))
+				if pos.distance(start) > DRAG_THRESHOLD =>
+			{
+				// start from here rather than jump by the threshold
+				Some(Pinch::Scrolling { last: pos })
+			}
+			(Some(Pinch::Undecided { held, .. }), Some(_)) if held + delta >= HOLD_TIME => {
+				gesture.left_click = Some(Click::Press);
+				Some(Pinch::Dragging)
+			}
+			(Some(Pinch::Undecided { start, held }), Some(_)) => Some(Pinch::Undecided {
+				start,
+				held: held + delta,
+			}),
+			(Some(Pinch::Undecided { .. }), None) => {
+				gesture.left_click = Some(Click::Tap);
+				None
+			}
+			(Some(Pinch::Dragging), Some(_)) => Some(Pinch::Dragging),
+			(Some(Pinch::Dragging), None) => {
+				gesture.left_click = Some(Click::Release);
+				None
+			}
+			(Some(Pinch::Scrolling { last }), Some(pos)) => {
+				moved = pos - last;
+				Some(Pinch::Scrolling { last: pos })
+			}
+			(Some(Pinch::Scrolling { .. }), None) | (None, None) => None,
+		};
+
+		// a pinch wins over two fingers
+		let two_fingers = input.two_fingers.filter(|_| self.pinch.is_none());
+		self.swipe = match (self.swipe, two_fingers) {
+			(None, Some(pos)) => Some(Swipe::Undecided { start: pos }),
+			(Some(Swipe::Undecided { start }), Some(pos))
+				if pos.distance(start) > SWIPE_THRESHOLD =>
+			{
+				Some(Swipe::Scrolling { last: pos })
+			}
+			(Some(Swipe::Undecided { start }), Some(_)) => Some(Swipe::Undecided { start }),
+			(Some(Swipe::Scrolling { last }), Some(pos)) => {
+				moved = pos - last;
+				Some(Swipe::Scrolling { last: pos })
+			}
+			(_, None) => None,
+		};
+
+		let scrolling = matches!(self.pinch, Some(Pinch::Scrolling { .. }))
+			|| matches!(self.swipe, Some(Swipe::Scrolling { .. }));
+		if scrolling {
+			gesture.scroll = moved;
+			if delta > 0.0 {
+				self.velocity = self.velocity.lerp(moved / delta, VELOCITY_SMOOTHING);
+			}
+		} else if self.pinch.is_some() || self.swipe.is_some() {
+			// catching the content stops it
+			self.velocity = Vec2::ZERO;
+		} else {
+			self.velocity *= (-INERTIA_FRICTION * delta).exp();
+			if self.velocity.length() < INERTIA_MIN_SPEED {
+				self.velocity = Vec2::ZERO;
+			}
+			gesture.scroll = self.velocity * delta;
+		}
+		gesture.holding_cursor = matches!(
+			self.pinch,
+			Some(Pinch::Undecided { .. } | Pinch::Scrolling { .. })
+		) || matches!(self.swipe, Some(Swipe::Scrolling { .. }));
+		gesture
+	}
+}
+
+#[test]
+fn pinch_presses_right_away() {
+	use glam::vec2;
+	let settings = HandGestureSettings::default();
+	let mut gestures = HandGestures::default();
+	let pinch = |pos| HandInput {
+		pinch: Some(pos),
+		two_fingers: None,
+	};
+	let gesture = gestures.update(&settings, pinch(vec2(0.1, 0.1)), 0.01);
+	assert_eq!(gesture.left_click, Some(Click::Press));
+	assert!(!gesture.holding_cursor);
+	// moving drags rather than scrolls
+	let gesture = gestures.update(&settings, pinch(vec2(0.1, 0.2)), 0.01);
+	assert_eq!(gesture.scroll, Vec2::ZERO);
+	let gesture = gestures.update(&settings, HandInput::default(), 0.01);
+	assert_eq!(gesture.left_click, Some(Click::Release));
+}
+
+#[test]
+fn quick_pinch_taps() {
+	use glam::vec2;
+	let settings = HandGestureSettings { pinch_scroll: true };
+	let mut gestures = HandGestures::default();
+	let pinch = |pos| HandInput {
+		pinch: Some(pos),
+		two_fingers: None,
+	};
+	gestures.update(&settings, pinch(vec2(0.1, 0.1)), 0.01);
+	gestures.update(&settings, pinch(vec2(0.105, 0.1)), 0.01);
+	let gesture = gestures.update(&settings, HandInput::default(), 0.01);
+	assert_eq!(gesture.left_click, Some(Click::Tap));
+	assert_eq!(gesture.scroll, Vec2::ZERO);
+}
+
+#[test]
+fn pinch_drag_scrolls_with_inertia() {
+	use glam::vec2;
+	let settings = HandGestureSettings { pinch_scroll: true };
+	let mut gestures = HandGestures::default();
+	let mut y = 0.0;
+	for _ in 0..20 {
+		let gesture = gestures.update(
+			&settings,
+			HandInput {
+				pinch: Some(vec2(0.0, y)),
+				two_fingers: None,
+			},
+			0.01,
+		);
+		assert_eq!(gesture.left_click, None);
+		y += 0.005;
+	}
+	let gesture = gestures.update(&settings, HandInput::default(), 0.01);
+	assert_eq!(gesture.left_click, None);
+	assert!(gesture.scroll.y > 0.0);
+	// eventually comes to a stop
+	let stopped = (0..1000)
+		.map(|_| {
+			gestures
+				.update(&settings, HandInput::default(), 0.01)
+				.scroll
+		})
+		.any(|scroll| scroll == Vec2::ZERO);
+	assert!(stopped);
+}
@@ -0,0 +1,72 @@
+use stardust_xr_asteroids::{Context, CreateInnerInfo, CustomElement, Transformable, ValidState};
+use stardust_xr_fusion::{
+	drawable::{Lines, LinesAspect},
+	node::NodeError,
+	spatial::{Spatial, SpatialRef, Transform},
+	values::{Color, Vector2},
+};
+use stardust_xr_molecules::lines::{rounded_rectangle, LineExt};
+
+/// An outline in the accent color around something, e.g. the focused panel.
+#[derive(Debug, PartialEq)]
+pub struct Highlight {
+	pub transform: Transform,
+	/// in meters
+	pub size: Vector2<f32>,
+	/// of the outline, in meters
+	pub thickness: f32,
+}
+impl<State: ValidState> CustomElement<State> for Highlight {
+	type Inner = HighlightInner;
+	type Resource = ();
+	type Error = NodeError;
+
+	fn create_inner(
+		&self,
+		context: &Context,
+		info: CreateInnerInfo,
+		_resource: &mut Self::Resource,
+	) -> Result<Self::Inner, Self::Error> {
+		let root = Spatial::create(info.parent_space, self.transform)?;
+		let inner = HighlightInner {
+			lines: Lines::create(&root, Transform::identity(), &[])?,
+			root,
+			color: context.accent_color.color(),
+		};
+		inner.draw(self.size, self.thickness);
+		Ok(inner)
+	}
+
+	fn diff(&self, old: &Self, inner: &mut Self::Inner, _resource: &mut Self::Resource) {
+		self.apply_transform(old, &inner.root);
+		if self.size != old.size || self.thickness != old.thickness {
+			inner.draw(self.size, self.thickness);
+		}
+	}
+
+	fn spatial_aspect(&self, inner: &Self::Inner) -> SpatialRef {
+		inner.root.clone().as_spatial_ref()
+	}
+}
+impl Transformable for Highlight {
+	fn transform(&self) -> &Transform {
+		&self.transform
+	}
+	fn transform_mut(&mut self) -> &mut Transform {
+		&mut self.transform
+	}
+}
+
+pub struct HighlightInner {
+	root: Spatial,
+	lines: Lines,
+	color: Color,
+}
+impl HighlightInner {
+	fn draw(&self, size: Vector2<f32>, thickness: f32) {
+		let outline = rounded_rectangle(size.x + 0.02, size.y + 0.02, 0.01, 4)
+			.thickness(thickness)
+			.color(self.color);
+		let _ = self.lines.set_lines(&[outline]);
+	}
+}
@@ -1,11 +1,17 @@
+use crate::{
+	panel_pose::{body_frame, glide, PanelPose},
+	snapping::{upright, UprightSettings},
+};
 use glam::{vec3, Quat, Vec3};
-use stardust_xr_asteroids::{Context, CreateInnerInfo, CustomElement, ValidState};
+use stardust_xr_asteroids::{Context, CreateInnerInfo, CustomElement, FnWrapper, ValidState};
 use stardust_xr_fusion::{
 	node::{NodeError, NodeResult, NodeType},
 	objects::hmd,
+	root::FrameInfo,
 	spatial::{Spatial, SpatialAspect, SpatialRef, SpatialRefAspect, Transform},
 };
 use std::f32::consts::PI;
+use tokio::sync::watch;
 
 fn look_direction(direction: Vec3) -> Quat {
 	let pitch = direction.y.asin();
@@ -13,12 +19,88 @@ fn look_direction(direction: Vec3) -> Quat {
 	Quat::from_rotation_y(-yaw - PI / 2.0) * Quat::from_rotation_x(pitch)
 }
 
-async fn initial_placement(spatial_root: Spatial) -> NodeResult<()> {
+/// Where a new panel goes when there's no pose to restore.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Placement {
+	/// `distance` meters in front of the user's head, facing them,
+	/// unless the app put the panel somewhere already, then it's just turned to face the user
+	InFront { distance: f32 },
+	/// relative to the client root
+	Stage(PanelPose),
+	/// relative to the user's body, see `PoseAnchor::Body`
+	Body(PanelPose),
+}
+impl Default for Placement {
+	fn default() -> Self {
+		Placement::InFront { distance: 0.25 }
+	}
+}
+
+/// How far in front of the user's head a panel flying in starts out, in meters.
+const FLY_IN_START: f32 = 0.1;
+
+async fn initial_placement(
+	spatial_root: Spatial,
+	pose: Option<PanelPose>,
+	placement: Placement,
+	fly_in: bool,
+	upright_settings: UprightSettings,
+) -> NodeResult<()> {
 	let client = spatial_root.client();
+	let root = client.get_root();
+
+	// we already know where this panel wants to be
+	if let Some(pose) = pose {
+		let _ = spatial_root.set_relative_transform(root, pose.transform());
+		return Ok(());
+	}
+
 	let Some(hmd) = hmd(client).await else {
 		return Err(NodeError::NotAliased);
 	};
-	let root = client.get_root();
+	let Ok(hmd_transform) = hmd.get_transform(root).await else {
+		return Err(NodeError::NotAliased);
+	};
+	let hmd_pose = PanelPose::from_transform(&hmd_transform).ok_or(NodeError::NotAliased)?;
+	let target = match placement {
+		Placement::InFront { distance } => {
+			let Some(pose) = in_front(&spatial_root, &hmd, distance, upright_settings).await?
+			else {
+				return Ok(());
+			};
+			pose
+		}
+		Placement::Stage(pose) => pose,
+		Placement::Body(pose) => {
+			let (body_translation, body_rotation) = body_frame(&hmd_transform);
+			PanelPose::new(
+				body_translation + body_rotation * pose.translation(),
+				body_rotation * pose.rotation(),
+			)
+		}
+	};
+	let target = upright(&upright_settings, target);
+	if fly_in {
+		let start = hmd_pose.compose(&PanelPose::new(
+			vec3(0.0, 0.0, -FLY_IN_START),
+			Quat::IDENTITY,
+		));
+		glide(&spatial_root, root, start, target, |_| {}).await;
+	} else {
+		let _ = spatial_root.set_relative_transform(root, target.transform());
+	}
+	Ok(())
+}
+
+/// The pose `distance` meters in front of the user's head,
+/// or none if the app's put the panel somewhere and it's just been turned to face the user.
+async fn in_front(
+	spatial_root: &Spatial,
+	hmd: &SpatialRef,
+	distance: f32,
+	upright_settings: UprightSettings,
+) -> NodeResult<Option<PanelPose>> {
+	let root = spatial_root.client().get_root();
 
 	let (
 		Ok(Transform {
@@ -38,26 +120,73 @@ async fn initial_placement(spatial_root: Spatial) -> NodeResult<()> {
 	if Vec3::from(item_translation.unwrap()).length_squared() < 0.001 {
 		println!("launched without a sense of space");
 		// so we want to position it in front of the user
-		let _ = spatial_root.set_relative_transform(
-			&hmd,
-			Transform::from_translation_rotation(vec3(0.0, 0.0, -0.25), Quat::IDENTITY),
-		);
-		return Ok(());
+		let hmd_pose = PanelPose::from_transform(&hmd.get_transform(root).await?)
+			.ok_or(NodeError::NotAliased)?;
+		return Ok(Some(hmd_pose.compose(&PanelPose::new(
+			vec3(0.0, 0.0, -distance),
+			Quat::IDENTITY,
+		))));
 	}
 
 	// otherwise make the panel look at the user
 	let look_rotation = look_direction(
 		(Vec3::from(item_translation.unwrap()) - Vec3::from(hmd_translation.unwrap())).normalize(),
 	);
+	let look_rotation =
+		upright(&upright_settings, PanelPose::new(Vec3::ZERO, look_rotation)).rotation();
 	let _ = spatial_root.set_relative_transform(root, Transform::from_rotation(look_rotation));
 
-	Ok(())
+	Ok(None)
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
-pub struct InitialPanelPlacement;
-impl<State: ValidState> CustomElement<State> for InitialPanelPlacement {
-	type Inner = Spatial;
+#[derive_where::derive_where(Debug, PartialEq)]
+#[allow(clippy::type_complexity)]
+pub struct InitialPanelPlacement<State: ValidState> {
+	/// pose relative to the client root to restore instead of guessing one
+	pub pose: Option<PanelPose>,
+	/// where to put it otherwise
+	pub placement: Placement,
+	/// glide over from just in front of the user instead of appearing in place
+	pub fly_in: bool,
+	/// how far the panel may lean where it's put
+	pub upright: UprightSettings,
+	/// called once the panel has been placed
+	pub on_placed: FnWrapper<dyn Fn(&mut State, PanelPose) + Send + Sync>,
+}
+impl<State: ValidState> InitialPanelPlacement<State> {
+	pub fn new(pose: Option<PanelPose>) -> Self {
+		InitialPanelPlacement {
+			pose,
+			placement: Placement::default(),
+			fly_in: false,
+			upright: UprightSettings::default(),
+			on_placed: FnWrapper(Box::new(|_, _| {})),
+		}
+	}
+	pub fn placement(mut self, placement: Placement) -> Self {
+		self.placement = placement;
+		self
+	}
+	pub fn fly_in(mut self, fly_in: bool) -> Self {
+		self.fly_in = fly_in;
+		self
+	}
+	pub fn upright(mut self, upright: UprightSettings) -> Self {
+		self.upright = upright;
+		self
+	}
+	pub fn on_placed(mut self, f: impl Fn(&mut State, PanelPose) + Send + Sync + 'static) -> Self {
+		self.on_placed = FnWrapper(Box::new(f));
+		self
+	}
+}
+
+pub struct InitialPanelPlacementInner {
+	spatial: Spatial,
+	placed: watch::Receiver<Option<PanelPose>>,
+}
+impl<State: ValidState> CustomElement<State> for InitialPanelPlacement<State> {
+	type Inner = InitialPanelPlacementInner;
 	type Resource = ();
 	type Error = NodeError;
 
@@ -68,13 +197,41 @@ impl<State: ValidState> CustomElement<State> for InitialPanelPlacement {
 		_resource: &mut Self::Resource,
 	) -> Result<Self::Inner, Self::Error> {
 		let spatial = Spatial::create(info.parent_space, Transform::identity())?;
-		tokio::task::spawn(initial_placement(spatial.clone()));
-		Ok(spatial)
+		let (placed_tx, placed) = watch::channel(None);
+		tokio::task::spawn({
+			let spatial = spatial.clone();
+			let pose = self.pose;
+			let placement = self.placement;
+			let fly_in = self.fly_in;
+			let upright_settings = self.upright;
+			async move {
+				initial_placement(spatial.clone(), pose, placement, fly_in, upright_settings)
+					.await?;
+				let transform = spatial.get_transform(spatial.client().get_root()).await?;
+				let _ = placed_tx.send(PanelPose::from_transform(&transform));
+				NodeResult::Ok(())
+			}
+		});
+		Ok(InitialPanelPlacementInner { spatial, placed })
 	}
 
 	fn diff(&self, _old_self: &Self, _inner: &mut Self::Inner, _resource: &mut Self::Resource) {}
 
+	fn frame(
+		&self,
+		_context: &Context,
+		_info: &FrameInfo,
+		state: &mut State,
+		inner: &mut Self::Inner,
+	) {
+		if inner.placed.has_changed().is_ok_and(|t| t) {
+			if let Some(pose) = *inner.placed.borrow_and_update() {
+				(self.on_placed.0)(state, pose);
+			}
+		}
+	}
+
 	fn spatial_aspect(&self, inner: &Self::Inner) -> SpatialRef {
-		inner.clone().as_spatial_ref()
+		inner.spatial.clone().as_spatial_ref()
 	}
 }
@@ -0,0 +1,130 @@
+use rustc_hash::FxHashMap;
+use stardust_xr_fusion::items::panel::SurfaceId;
+
+/// A key pressed or let go of, as it reached a surface of a panel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Key {
+	pub surface: SurfaceId,
+	pub keymap_id: u64,
+	pub key: u32,
+	pub pressed: bool,
+}
+
+/// Sends keys on from whichever panels' keyboard handlers they reached:
+/// presses to the focused panel, and releases to wherever the key was pressed.
+/// A key that reached several panels at once still only gets sent once.
+#[derive(Debug, Default)]
+pub struct KeyRouter {
+	/// which panel and surface each key held down was pressed into
+	held: FxHashMap<u32, (u64, SurfaceId)>,
+}
+impl KeyRouter {
+	/// Which panel each of the keys that `reached` panels this frame goes to.
+	pub fn route(
+		&mut self,
+		mut reached: Vec<(u64, Vec<Key>)>,
+		focused: Option<u64>,
+	) -> Vec<(u64, Key)> {
+		// the focused panel's own keys keep the surface they were typed into
+		reached.sort_by_key(|(uid, _)| Some(*uid) != focused);
+		let mut seen = Vec::new();
+		let mut routed = Vec::new();
+		for (uid, keys) in reached {
+			// every keyboard handler a keyboard reaches gets the same keys, so they're only sent once
+			let events = keys
+				.iter()
+				.map(|key| (key.keymap_id, key.key, key.pressed))
+				.collect::<Vec<_>>();
+			if seen.contains(&events) {
+				continue;
+			}
+			seen.push(events);
+			for key in keys {
+				if key.pressed {
+					let Some(target) = focused else {
+						continue;
+					};
+					// already down from another keyboard, it's let go of once
+					if self.held.contains_key(&key.key) {
+						continue;
+					}
+					let surface = if target == uid {
+						key.surface
+					} else {
+						SurfaceId::Toplevel(())
+					};
+					self.held.insert(key.key, (target, surface));
+					routed.push((target, Key { surface, ..key }));
+				} else if let Some((target, surface)) = self.held.remove(&key.key) {
+					routed.push((target, Key { surface, ..key }));
+				}
+			}
+		}
+		routed
+	}
+
+	/// Let go of the keys held in a panel that's gone.
+	pub fn forget(&mut self, uid: u64) {
+		self.held.retain(|_, (target, _)| *target != uid);
+	}
+}
+
+#[test]
+fn releases_go_where_pressed() {
+	let key = |surface, key, pressed| Key {
+		surface,
+		keymap_id: 0,
+		key,
+		pressed,
+	};
+	let popup = SurfaceId::Child(1);
+	let mut router = KeyRouter::default();
+	// typed into a popup of the focused panel
+	assert_eq!(
+		router.route(vec![(1, vec![key(popup, 30, true)])], Some(1)),
+		vec![(1, key(popup, 30, true))]
+	);
+	// focus moves on while the key is held, the release still goes to the popup
+	let routed = router.route(
+		vec![(2, vec![key(SurfaceId::Toplevel(()), 30, false)])],
+		Some(2),
+	);
+	assert_eq!(routed, vec![(1, key(popup, 30, false))]);
+	// a release for a key that isn't down goes nowhere
+	assert!(router
+		.route(
+			vec![(2, vec![key(SurfaceId::Toplevel(()), 30, false)])],
+			Some(2)
+		)
+		.is_empty());
+}
+
+#[test]
+fn keys_reaching_several_panels_are_sent_once() {
+	let key = |surface, key, pressed| Key {
+		surface,
+		keymap_id: 0,
+		key,
+		pressed,
+	};
+	let toplevel = SurfaceId::Toplevel(());
+	let mut router = KeyRouter::default();
+	let typed = vec![
+		key(toplevel, 30, true),
+		key(toplevel, 30, false),
+		key(toplevel, 30, true),
+	];
+	let routed = router.route(vec![(2, typed.clone()), (1, typed)], Some(1));
+	assert_eq!(
+		routed,
+		vec![
+			(1, key(toplevel, 30, true)),
+			(1, key(toplevel, 30, false)),
+			(1, key(toplevel, 30, true)),
+		]
+	);
+	// only one release for it too
+	let released = vec![key(toplevel, 30, false)];
+	let routed = router.route(vec![(1, released.clone()), (2, released)], Some(1));
+	assert_eq!(routed, vec![(1, key(toplevel, 30, false))]);
+}
@@ -1,12 +1,30 @@
+use action_button::ActionButton;
+use anchoring::Anchor;
 use close_button::ExposureButton;
-use glam::{vec2, Quat};
-use initial_panel_placement::InitialPanelPlacement;
+use curved_panel::{CurvedPanelModel, DEFAULT_CURVE_RADIUS};
+use dock::Dock;
+use frame_hook::FrameHook;
+use gaze::{gaze_distance, GazeTracker};
+use glam::{vec2, vec3, Quat, Vec2, Vec3};
+use hand_gestures::HandGestureSettings;
+use highlight::Highlight;
+use initial_panel_placement::{InitialPanelPlacement, Placement};
 use initial_positioner::InitialPositioner;
+use keyboard::{Key, KeyRouter};
+use overview::{overview, overview_pose};
+use panel_pose::{body_frame, PanelPose, PoseAnchor, PoseRequest};
+use panel_shell_transfer::{Acceptors, PanelShellTransferBall};
 use panel_wrapper::PanelWrapper;
-use pointer_input::PointerPlane;
-use resize_handles::ResizeHandles;
-use rustc_hash::FxHashMap;
+use pointer_filter::PointerFilterSettings;
+use pointer_input::{PointerPlane, ScrollEvent};
+use preferences::{
+	FullscreenSettings, PinchZoom, PlacementSettings, PlacementStrategy, ToplevelPreferences,
+	TouchMode, DEFAULT_DENSITY,
+};
+use resize_handles::{ClientDrag, ResizeHandles};
+use rustc_hash::{FxHashMap, FxHashSet};
 use serde::{Deserialize, Serialize};
+use snapping::{upright, SnapSettings, SnapTarget, Upright, UprightSettings};
 use stardust_xr_asteroids::{
 	client::{run, ClientState},
 	elements::{
@@ -17,25 +35,49 @@ use stardust_xr_asteroids::{
 };
 use stardust_xr_fusion::{
 	drawable::{TextBounds, TextFit, XAlign, YAlign},
-	fields::Shape,
 	items::panel::{ChildInfo, Geometry, PanelItem, PanelItemAspect, SurfaceId, ToplevelInfo},
 	node::NodeType,
 	spatial::Transform,
 	values::Vector2,
 };
-use std::f32::consts::FRAC_PI_2;
+use std::{
+	f32::consts::{FRAC_PI_2, FRAC_PI_4},
+	time::{Duration, Instant},
+};
+use tiling::TilingSettings;
+use touch_gestures::{TouchGesture, ZoomSteps};
 use touch_input::TouchPlane;
 use tracing_subscriber::{layer::SubscriberExt as _, EnvFilter};
+use tray::tray;
+use workspace_bar::workspace_bar;
 
+pub mod action_button;
+pub mod anchoring;
 pub mod close_button;
+pub mod curved_panel;
+pub mod dock;
+pub mod frame_hook;
+pub mod gaze;
 pub mod grab_ball;
+pub mod hand_gestures;
+pub mod highlight;
 pub mod initial_panel_placement;
 pub mod initial_positioner;
+pub mod keyboard;
+pub mod overview;
+pub mod panel_pose;
 pub mod panel_shell_transfer;
 pub mod panel_wrapper;
+pub mod pointer_filter;
 pub mod pointer_input;
+pub mod preferences;
 pub mod resize_handles;
+pub mod snapping;
+pub mod tiling;
+pub mod touch_gestures;
 pub mod touch_input;
+pub mod tray;
+pub mod workspace_bar;
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() {
@@ -110,22 +152,644 @@ pub fn process_initial_children(children: Vec<ChildInfo>) -> Vec<ChildState> {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct State {
-	#[serde(skip)]
-	_toplevel_preferences: FxHashMap<String, f32>,
+	/// keyed by app_id
+	#[serde(default)]
+	toplevel_preferences: FxHashMap<String, ToplevelPreferences>,
 	mouse_scroll_multiplier: f32,
+	#[serde(default)]
+	fullscreen: FullscreenSettings,
+	/// resize handles on the middle of each edge as well as the corners
+	#[serde(default)]
+	edge_resize_handles: bool,
+	/// how the cursor follows pointers over panels
+	#[serde(default)]
+	pointer_filter: PointerFilterSettings,
+	/// how hands click and scroll on panels
+	#[serde(default)]
+	hand_gestures: HandGestureSettings,
+	#[serde(default)]
+	snapping: SnapSettings,
+	/// how far panels may lean
+	#[serde(default)]
+	upright: UprightSettings,
+	#[serde(default)]
+	tiling: TilingSettings,
+	/// where new toplevels go
+	#[serde(default)]
+	placement: PlacementSettings,
+	/// focus whichever panel the user has been looking at for a moment
+	#[serde(default)]
+	focus_follows_gaze: bool,
+	/// names of the workspaces, in order
+	#[serde(default = "default_workspaces")]
+	workspaces: Vec<String>,
+	/// the workspace being shown
+	#[serde(default)]
+	current_workspace: String,
+	/// the toplevel that gets typed into
+	#[serde(skip)]
+	focused: Option<u64>,
+	/// the toplevel the user's looking at and since when
+	#[serde(skip)]
+	gazed_at: Option<(u64, Instant)>,
+	/// where the user's head last was, if it's being tracked
+	#[serde(skip)]
+	head: Option<PanelPose>,
+	/// showing thumbnails of every toplevel to pick from
+	#[serde(skip)]
+	overview: bool,
+	/// every toplevel in the order it takes tiling slots
+	#[serde(skip)]
+	tiling_order: Vec<u64>,
+	/// the toplevels that were last laid out, in order
+	#[serde(skip)]
+	tiled: Vec<u64>,
+	/// the user's position and heading when tiling was turned on, which the tiles stay laid out around
+	#[serde(skip)]
+	tiling_frame: Option<PanelPose>,
+	/// every panel to line up with, as last shared with the toplevels
+	#[serde(skip)]
+	snap_targets: Vec<(u64, SnapTarget)>,
+	/// where typing goes
+	#[serde(skip)]
+	keys: KeyRouter,
 	#[serde(skip)]
 	toplevels: FxHashMap<u64, ToplevelState>,
-	// acceptors: FxHashMap<u64, (PanelItemAcceptor, Field)>,
+	#[serde(skip)]
+	acceptors: Acceptors,
 }
 impl Default for State {
 	fn default() -> Self {
 		State {
 			toplevels: FxHashMap::default(),
-			_toplevel_preferences: FxHashMap::default(),
+			acceptors: Acceptors::default(),
+			toplevel_preferences: FxHashMap::default(),
 			mouse_scroll_multiplier: 1.0,
+			fullscreen: FullscreenSettings::default(),
+			edge_resize_handles: false,
+			pointer_filter: PointerFilterSettings::default(),
+			hand_gestures: HandGestureSettings::default(),
+			snapping: SnapSettings::default(),
+			upright: UprightSettings::default(),
+			tiling: TilingSettings::default(),
+			placement: PlacementSettings::default(),
+			focus_follows_gaze: false,
+			focused: None,
+			gazed_at: None,
+			head: None,
+			workspaces: default_workspaces(),
+			current_workspace: String::new(),
+			overview: false,
+			tiling_order: Vec::new(),
+			tiled: Vec::new(),
+			tiling_frame: None,
+			snap_targets: Vec::new(),
+			keys: KeyRouter::default(),
 		}
 	}
 }
+fn default_workspaces() -> Vec<String> {
+	["1", "2", "3", "4"].map(String::from).to_vec()
+}
+/// label, whether it's lit up, and what pressing it does
+type ToolbarButton = (&'static str, bool, fn(&mut ToplevelState));
+/// in meters
+const TOOLBAR_BUTTON_SIZE: [f32; 2] = [0.04, 0.015];
+
+/// How long smooth scrolling from a mouse or touchpad has to pause for to count as stopped.
+const MOUSE_SCROLL_STOP_DELAY: Duration = Duration::from_millis(100);
+
+/// How far pinching two fingers can scale a window up or down, in px per meter.
+const MIN_ZOOM_DENSITY: f32 = 500.0;
+const MAX_ZOOM_DENSITY: f32 = 12000.0;
+
+/// How close an app's smallest and largest aspect ratios have to be to count as one it's locked to, relatively.
+const ASPECT_TOLERANCE: f32 = 0.01;
+
+/// How far in front of its parent a dialog floats, in meters.
+const DIALOG_DEPTH: f32 = 0.02;
+
+/// How far in front of the user summoned panels end up, in meters.
+const SUMMON_DISTANCE: f32 = 0.5;
+/// Space left between panels put side by side, in meters.
+const PANEL_GAP: f32 = 0.03;
+
+/// How long the user has to look at a panel for it to be focused, with focus-follows-gaze.
+const GAZE_DWELL: Duration = Duration::from_millis(400);
+/// How far in front of the panel under the cursor `PlacementStrategy::Pointer` puts new panels, in meters.
+const POINTER_PLACEMENT_DEPTH: f32 = 0.05;
+
+/// Where Flatland's own controls float, relative to the user's body: low and in front, tilted up.
+fn dock_pose() -> PanelPose {
+	PanelPose::new(vec3(0.0, -0.4, -0.4), Quat::from_rotation_x(-FRAC_PI_4))
+}
+
+impl State {
+	/// The toplevel a dialog belongs to, if it's still around.
+	fn parent_of(&self, uid: u64) -> Option<u64> {
+		let parent = self.toplevels.get(&uid)?.info.parent?;
+		(parent != uid && self.toplevels.contains_key(&parent)).then_some(parent)
+	}
+	/// Whether a toplevel and every parent above it are shown.
+	fn stack_enabled(&self, uid: u64) -> bool {
+		let mut uid = uid;
+		// bounded in case a client manages to make a cycle
+		for _ in 0..self.toplevels.len() {
+			let Some(toplevel) = self.toplevels.get(&uid) else {
+				return true;
+			};
+			if !toplevel.enabled {
+				return false;
+			}
+			let Some(parent) = self.parent_of(uid) else {
+				return true;
+			};
+			uid = parent;
+		}
+		true
+	}
+	/// The workspace after `name`, wrapping around.
+	fn next_workspace(&self, name: &str) -> String {
+		let index = self
+			.workspaces
+			.iter()
+			.position(|w| w == name)
+			.unwrap_or_default();
+		self.workspaces[(index + 1) % self.workspaces.len()].clone()
+	}
+	/// Move toplevels that asked for it from the toolbar to the next workspace.
+	fn move_workspaces(&mut self) {
+		let moves = self
+			.toplevels
+			.iter_mut()
+			.filter_map(|(uid, t)| {
+				std::mem::take(&mut t.move_workspace).then(|| (*uid, t.workspace.clone()))
+			})
+			.collect::<Vec<_>>();
+		for (uid, workspace) in moves {
+			let next = self.next_workspace(&workspace);
+			if let Some(toplevel) = self.toplevels.get_mut(&uid) {
+				toplevel.workspace = next;
+			}
+		}
+	}
+	/// Only show toplevels on the current workspace that haven't been captured.
+	fn update_visibility(&mut self) {
+		if self.workspaces.is_empty() {
+			self.workspaces = default_workspaces();
+		}
+		if !self.workspaces.contains(&self.current_workspace) {
+			self.current_workspace = self.workspaces[0].clone();
+		}
+		// dialogs go wherever their parent is
+		let parent_workspaces = self
+			.toplevels
+			.keys()
+			.filter_map(|uid| {
+				let parent = self.parent_of(*uid)?;
+				Some((*uid, self.toplevels.get(&parent)?.workspace.clone()))
+			})
+			.collect::<Vec<_>>();
+		for (uid, workspace) in parent_workspaces {
+			if let Some(dialog) = self.toplevels.get_mut(&uid) {
+				dialog.workspace = workspace;
+			}
+		}
+		for toplevel in self.toplevels.values_mut() {
+			if !self.workspaces.contains(&toplevel.workspace) {
+				toplevel.workspace.clone_from(&self.current_workspace);
+			}
+			toplevel.enabled = !toplevel.captured
+				&& !toplevel.minimized
+				&& toplevel.workspace == self.current_workspace;
+			// a hidden panel's resize handles go away, so don't replay an old request once they're back
+			if !toplevel.enabled {
+				toplevel.pose_request.take();
+			}
+		}
+	}
+	/// Leave the overview and highlight a toplevel, either brought in front of the user
+	/// onto the current workspace or left where it is with its workspace switched to.
+	fn show_toplevel(&mut self, uid: u64, bring_in_front: bool) {
+		self.overview = false;
+		for toplevel in self.toplevels.values_mut() {
+			toplevel.highlighted = false;
+		}
+		let current = self.current_workspace.clone();
+		let Some(toplevel) = self.toplevels.get_mut(&uid) else {
+			return;
+		};
+		toplevel.minimized = false;
+		toplevel.highlighted = true;
+		toplevel.last_used.replace(Instant::now());
+		if bring_in_front {
+			toplevel.workspace = current;
+		} else {
+			self.current_workspace.clone_from(&toplevel.workspace);
+		}
+		self.update_visibility();
+		let Some(toplevel) = self.toplevels.get_mut(&uid) else {
+			return;
+		};
+		// tiles and fullscreen panels have their own place
+		if bring_in_front && !toplevel.tiled && toplevel.fullscreen.is_none() {
+			toplevel.summon(0.0);
+		}
+	}
+	/// Bring every panel on the current workspace in front of the user, side by side.
+	fn summon_all(&mut self) {
+		let uids = self
+			.tiling_order
+			.iter()
+			.copied()
+			.filter(|uid| {
+				self.parent_of(*uid).is_none()
+					&& self
+						.toplevels
+						.get(uid)
+						.is_some_and(|t| t.enabled && !t.tiled && t.fullscreen.is_none())
+			})
+			.collect::<Vec<_>>();
+		let widths = uids
+			.iter()
+			.map(|uid| self.toplevels[uid].size_meters().x)
+			.collect::<Vec<_>>();
+		let total = widths.iter().sum::<f32>() + PANEL_GAP * widths.len().saturating_sub(1) as f32;
+		let mut left = -total / 2.0;
+		for (uid, width) in uids.into_iter().zip(widths) {
+			if let Some(toplevel) = self.toplevels.get_mut(&uid) {
+				toplevel.summon(left + width / 2.0);
+			}
+			left += width + PANEL_GAP;
+		}
+	}
+	/// Focus the shown toplevel that was clicked, touched, let go of or picked last.
+	fn update_focus(&mut self) {
+		self.focused = self
+			.toplevels
+			.iter()
+			.filter(|(uid, t)| t.last_used.is_some() && self.stack_enabled(**uid))
+			.max_by_key(|(_, t)| t.last_used)
+			.map(|(uid, _)| *uid);
+		for (uid, toplevel) in self.toplevels.iter_mut() {
+			let focused = self.focused == Some(*uid);
+			if toplevel.focused != focused {
+				toplevel.focused = focused;
+			}
+		}
+	}
+	fn focus(&mut self, uid: u64) {
+		if let Some(toplevel) = self.toplevels.get_mut(&uid) {
+			toplevel.last_used.replace(Instant::now());
+		}
+	}
+	/// Smooth scrolling from a mouse or touchpad doesn't say when it ends, so end it once it pauses.
+	fn stop_mouse_scrolls(&mut self) {
+		for toplevel in self.toplevels.values_mut() {
+			if toplevel
+				.mouse_scrolling
+				.is_some_and(|(_, since)| since.elapsed() >= MOUSE_SCROLL_STOP_DELAY)
+			{
+				toplevel.stop_mouse_scroll();
+			}
+		}
+	}
+	/// Pass keys that reached any panel on to the focused one,
+	/// and releases to wherever the key was pressed.
+	fn route_keys(&mut self) {
+		let reached = self
+			.toplevels
+			.iter_mut()
+			.filter(|(_, toplevel)| !toplevel.keys.is_empty())
+			.map(|(uid, toplevel)| (*uid, std::mem::take(&mut toplevel.keys)))
+			.collect::<Vec<_>>();
+		if reached.is_empty() {
+			return;
+		}
+		for (uid, key) in self.keys.route(reached, self.focused) {
+			if let Some(toplevel) = self.toplevels.get_mut(&uid) {
+				toplevel.send_key(key);
+			}
+		}
+	}
+	/// The focused toplevel, or the one it's a dialog of.
+	fn focused_toplevel(&self) -> Option<&ToplevelState> {
+		let uid = self.focused?;
+		self.toplevels.get(&self.parent_of(uid).unwrap_or(uid))
+	}
+	/// Keep up with where the user's head is, for tiling and focus-follows-gaze.
+	fn track_head(&mut self, head: PanelPose) {
+		self.head.replace(head);
+		if self.focus_follows_gaze {
+			self.follow_gaze(head);
+		}
+	}
+	/// Focus the panel the user's looking at once they've looked at it long enough.
+	fn follow_gaze(&mut self, head: PanelPose) {
+		let looked_at = self
+			.toplevels
+			.iter()
+			.filter(|(uid, _)| self.stack_enabled(**uid))
+			.filter_map(|(uid, t)| {
+				Some((*uid, gaze_distance(head, t.pose?, t.size_meters().into())?))
+			})
+			.min_by(|(_, a), (_, b)| a.total_cmp(b))
+			.map(|(uid, _)| uid);
+		match (looked_at, self.gazed_at) {
+			(Some(uid), Some((gazed_at, since))) if uid == gazed_at => {
+				if since.elapsed() >= GAZE_DWELL && self.focused != Some(uid) {
+					self.focus(uid);
+				}
+			}
+			_ => self.gazed_at = looked_at.map(|uid| (uid, Instant::now())),
+		}
+	}
+	/// Where a new toplevel of `size` in meters goes, if it has no pose to restore.
+	fn placement(&self, strategy: PlacementStrategy, size: Vec2) -> Placement {
+		let in_front = Placement::InFront {
+			distance: self.placement.distance,
+		};
+		let placement = match strategy {
+			PlacementStrategy::Remembered | PlacementStrategy::InFront => None,
+			PlacementStrategy::NextToFocused => self.focused_toplevel().and_then(|toplevel| {
+				let offset = toplevel.size_meters().x / 2.0 + PANEL_GAP + size.x / 2.0;
+				Some(Placement::Stage(toplevel.pose?.compose(&PanelPose::new(
+					vec3(offset, 0.0, 0.0),
+					Quat::IDENTITY,
+				))))
+			}),
+			PlacementStrategy::TilingSlot => {
+				let (_, slot) = self.free_tiling_slot();
+				slot.map(|slot| match self.tiling_frame {
+					Some(frame) => Placement::Stage(frame.compose(&slot.pose)),
+					// the head hasn't been found yet, or nothing's tiled to lay the slots out around
+					None => Placement::Body(slot.pose),
+				})
+			}
+			PlacementStrategy::Pointer => self.focused_toplevel().and_then(|toplevel| {
+				let size_px = vec2(toplevel.info.size.x as f32, toplevel.info.size.y as f32);
+				let cursor = vec2(toplevel.cursor_pos.x, toplevel.cursor_pos.y);
+				let cursor = (cursor - size_px / 2.0) * vec2(1.0, -1.0) / toplevel.density;
+				let (pos, rot) = toplevel.on_panel(cursor.extend(POINTER_PLACEMENT_DEPTH));
+				Some(Placement::Stage(
+					toplevel.pose?.compose(&PanelPose::new(pos, rot)),
+				))
+			}),
+		};
+		placement.unwrap_or(in_front)
+	}
+	/// Where in the tiling order a new toplevel should go and the slot it'd get there:
+	/// the first one with none of the tiles already in it, so it doesn't land on top of one.
+	fn free_tiling_slot(&self) -> (usize, Option<tiling::Slot>) {
+		let tiles = self
+			.tiling_order
+			.iter()
+			.filter(|uid| self.tileable(**uid))
+			.filter_map(|uid| self.toplevels.get(uid))
+			.collect::<Vec<_>>();
+		let slots = tiling::slots(&self.tiling, tiles.len() + 1);
+		let free = self.tiling_frame.and_then(|frame| {
+			slots.iter().position(|slot| {
+				let pose = frame.compose(&slot.pose);
+				!tiles.iter().any(|tile| {
+					tile.pose.is_some_and(|tile_pose| {
+						let size = Vec2::from(tile.size_meters()).min(slot.size);
+						tile_pose.translation().distance(pose.translation())
+							< size.x.min(size.y) / 2.0
+					})
+				})
+			})
+		});
+		let index = free.unwrap_or(tiles.len());
+		(index, slots.get(index).copied())
+	}
+	/// Keep dialogs in front of their parent, carrying them along when the parent moves.
+	fn arrange_dialogs(&mut self) {
+		let parent_poses = self
+			.toplevels
+			.keys()
+			.filter_map(|uid| {
+				let parent = self.parent_of(*uid)?;
+				Some((*uid, self.toplevels.get(&parent)?.pose?))
+			})
+			.collect::<Vec<_>>();
+		for (uid, parent_pose) in parent_poses {
+			let Some(dialog) = self.toplevels.get_mut(&uid) else {
+				continue;
+			};
+			if dialog.parent_pose == Some(parent_pose) || dialog.fullscreen.is_some() {
+				continue;
+			}
+			let pose = match (dialog.parent_pose, dialog.pose) {
+				(Some(old_parent_pose), Some(pose)) => {
+					parent_pose.compose(&old_parent_pose.inverse().compose(&pose))
+				}
+				_ => dialog_pose(&parent_pose),
+			};
+			dialog.parent_pose.replace(parent_pose);
+			dialog
+				.pose_request
+				.replace(PoseRequest::new(PoseAnchor::Stage, pose));
+		}
+	}
+	/// Let every toplevel know where it could be handed over to.
+	fn share_acceptors(&mut self) {
+		for toplevel in self.toplevels.values_mut() {
+			toplevel.acceptors.clone_from(&self.acceptors);
+		}
+	}
+	/// Whether a toplevel takes part in tiling: shown, not a dialog and not fullscreen.
+	fn tileable(&self, uid: u64) -> bool {
+		self.stack_enabled(uid)
+			&& self.parent_of(uid).is_none()
+			&& self
+				.toplevels
+				.get(&uid)
+				.is_some_and(|t| t.fullscreen.is_none())
+	}
+	/// Lay out the toplevels when tiling, whenever one comes or goes or gets dropped somewhere.
+	fn arrange_tiles(&mut self) {
+		if !self.tiling.enabled {
+			for uid in self.tiled.drain(..) {
+				if let Some(toplevel) = self.toplevels.get_mut(&uid) {
+					toplevel.tiled = false;
+				}
+			}
+			self.tiling_frame.take();
+			return;
+		}
+		// dropping a tile onto another swaps them, dropping it anywhere else puts it back
+		let mut dropped = false;
+		for uid in self.tiled.clone() {
+			let Some(pose) = self
+				.toplevels
+				.get_mut(&uid)
+				.and_then(|t| t.released_pose.take())
+			else {
+				continue;
+			};
+			dropped = true;
+			let target = self
+				.tiled
+				.iter()
+				.filter(|other| **other != uid)
+				.filter_map(|other| {
+					let toplevel = self.toplevels.get(other)?;
+					let distance = toplevel.pose?.translation().distance(pose.translation());
+					let size = toplevel.size_meters();
+					(distance < size.x.min(size.y) / 2.0).then_some((*other, distance))
+				})
+				.min_by(|(_, a), (_, b)| a.total_cmp(b));
+			let Some((other, _)) = target else {
+				continue;
+			};
+			let a = self.tiling_order.iter().position(|t| *t == uid);
+			let b = self.tiling_order.iter().position(|t| *t == other);
+			if let (Some(a), Some(b)) = (a, b) {
+				self.tiling_order.swap(a, b);
+			}
+		}
+
+		let tiles = self
+			.tiling_order
+			.iter()
+			.copied()
+			.filter(|uid| self.tileable(*uid))
+			.collect::<Vec<_>>();
+		if !dropped && tiles == self.tiled {
+			return;
+		}
+		for uid in &self.tiled {
+			if !tiles.contains(uid) {
+				if let Some(toplevel) = self.toplevels.get_mut(uid) {
+					toplevel.tiled = false;
+				}
+			}
+		}
+		// stay laid out around where the user was when tiling was turned on, rather than turning with them
+		if self.tiling_frame.is_none() {
+			self.tiling_frame = self.head.map(|head| {
+				let (translation, rotation) = body_frame(&head.transform());
+				PanelPose::new(translation, rotation)
+			});
+		}
+		let frame = self.tiling_frame;
+		let slots = tiling::slots(&self.tiling, tiles.len());
+		for (uid, slot) in tiles.iter().zip(slots) {
+			let Some(toplevel) = self.toplevels.get_mut(uid) else {
+				continue;
+			};
+			toplevel.tiled = true;
+			toplevel.fit_to(slot.size);
+			toplevel.pose_request.replace(match frame {
+				Some(frame) => PoseRequest::new(PoseAnchor::Stage, frame.compose(&slot.pose)),
+				// the head hasn't been found yet
+				None => PoseRequest::new(PoseAnchor::Body, slot.pose),
+			});
+		}
+		self.tiled = tiles;
+	}
+	/// Let every toplevel know where the others are, to line up with when it's let go of.
+	fn share_snap_targets(&mut self) {
+		let targets = self
+			.toplevels
+			.iter()
+			// dialogs already sit right on top of their parent
+			.filter(|(uid, t)| {
+				self.stack_enabled(**uid)
+					&& self.parent_of(**uid).is_none()
+					&& t.fullscreen.is_none()
+			})
+			.filter_map(|(uid, t)| {
+				Some((
+					*uid,
+					SnapTarget {
+						pose: t.pose?,
+						size: t.size_meters().into(),
+					},
+				))
+			})
+			.collect::<Vec<_>>();
+		// only hand them out again once something moved, resized, came or went
+		if targets == self.snap_targets {
+			return;
+		}
+		self.snap_targets = targets;
+		for (uid, toplevel) in self.toplevels.iter_mut() {
+			let others = self
+				.snap_targets
+				.iter()
+				.filter(|(other, _)| other != uid)
+				.map(|(_, target)| *target)
+				.collect::<Vec<_>>();
+			if toplevel.snap_targets != others {
+				toplevel.snap_targets = others;
+			}
+		}
+	}
+	/// Load preferences into toplevels whose app_id showed up late and store the latest preferences of the rest.
+	fn sync_preferences(&mut self) {
+		let uids = self.toplevels.keys().copied().collect::<Vec<_>>();
+		for uid in uids {
+			let toplevel = &self.toplevels[&uid];
+			// dialogs would clobber the size and place of the app's main window
+			if toplevel.info.parent.is_some() {
+				continue;
+			}
+			let Some(app_id) = toplevel.info.app_id.clone() else {
+				continue;
+			};
+			if !toplevel.preferences_loaded {
+				// a second window of the same app shouldn't land right on top of the first
+				let app_already_open = self
+					.toplevels
+					.iter()
+					.any(|(other, t)| *other != uid && t.info.app_id.as_ref() == Some(&app_id));
+				let toplevel = self.toplevels.get_mut(&uid).unwrap();
+				if let Some(preferences) = self.toplevel_preferences.get(&app_id) {
+					toplevel.apply_preferences(preferences);
+					let strategy = toplevel
+						.placement_strategy
+						.unwrap_or(self.placement.strategy);
+					// the panel's already placed, so it has to be moved rather than just told where it is
+					if let Some(pose) = preferences
+						.pose
+						.filter(|_| !app_already_open && strategy == PlacementStrategy::Remembered)
+					{
+						let anchor = match toplevel.anchor {
+							Anchor::World => PoseAnchor::Stage,
+							Anchor::Body | Anchor::Head => {
+								toplevel.anchor_offset.replace(pose);
+								PoseAnchor::Body
+							}
+						};
+						toplevel
+							.pose_request
+							.replace(PoseRequest::new(anchor, pose));
+					}
+				}
+				toplevel.preferences_loaded = true;
+				continue;
+			}
+			let toplevel = &self.toplevels[&uid];
+			let mut preferences = toplevel.preferences();
+			// a tile's slot isn't where the app usually goes
+			if toplevel.tiled {
+				preferences.pose = self.toplevel_preferences.get(&app_id).and_then(|p| p.pose);
+			}
+			if self.toplevel_preferences.get(&app_id) != Some(&preferences) {
+				self.toplevel_preferences.insert(app_id, preferences);
+			}
+		}
+	}
+}
+/// Centered on the parent, just in front of it.
+fn dialog_pose(parent_pose: &PanelPose) -> PanelPose {
+	parent_pose.compose(&PanelPose::new(
+		vec3(0.0, 0.0, DIALOG_DEPTH),
+		Quat::IDENTITY,
+	))
+}
 impl Migrate for State {
 	type Old = Self;
 }
@@ -140,43 +804,183 @@ impl Reify for State {
 	) -> impl stardust_xr_asteroids::Element<Self> {
 		PanelUI::<State> {
 			on_create_item: FnWrapper(Box::new(|state, item, data| {
-				state.toplevels.insert(
-					item.id(),
-					ToplevelState {
-						enabled: true,
-						panel_item: item,
-						info: data.toplevel,
-						cursor_pos: [0.0; 2].into(),
-						cursor: None,
-						children: process_initial_children(data.children),
-						density: 3000.0,
-						mouse_scroll_multiplier: state.mouse_scroll_multiplier,
-					},
-				);
+				let preferences = data
+					.toplevel
+					.app_id
+					.as_ref()
+					.filter(|_| data.toplevel.parent.is_none())
+					.and_then(|app_id| state.toplevel_preferences.get(app_id))
+					.cloned();
+				// a second window of the same app shouldn't land right on top of the first
+				let app_already_open = state
+					.toplevels
+					.values()
+					.any(|t| t.info.app_id.is_some() && t.info.app_id == data.toplevel.app_id);
+				let parent_pose = data
+					.toplevel
+					.parent
+					.and_then(|parent| state.toplevels.get(&parent))
+					.and_then(|parent| parent.pose);
+				let mut toplevel = ToplevelState {
+					enabled: true,
+					captured: false,
+					workspace: state.current_workspace.clone(),
+					move_workspace: false,
+					minimized: false,
+					highlighted: false,
+					focused: false,
+					panel_item: item.clone(),
+					info: data.toplevel,
+					cursor_pos: [0.0; 2].into(),
+					cursor: None,
+					children: process_initial_children(data.children),
+					density: DEFAULT_DENSITY,
+					mouse_scroll_multiplier: state.mouse_scroll_multiplier,
+					pose: None,
+					pose_request: None,
+					client_drag: None,
+					acceptors: state.acceptors.clone(),
+					parent_pose,
+					preferences_loaded: preferences.is_some(),
+					fullscreen_settings: state.fullscreen,
+					snap_settings: state.snapping,
+					upright: state.upright,
+					snap_targets: Vec::new(),
+					tiled: false,
+					released_pose: None,
+					edge_handles: state.edge_resize_handles,
+					pointer_filter: state.pointer_filter.clone(),
+					hand_gestures: state.hand_gestures,
+					aspect_lock: false,
+					scale_mode: false,
+					curve_radius: None,
+					anchor: Anchor::World,
+					anchor_offset: None,
+					touch_mode: TouchMode::Touch,
+					pinch_zoom: PinchZoom::CtrlScroll,
+					zoom_steps: ZoomSteps::default(),
+					keymap_id: None,
+					placement_strategy: None,
+					placement: Placement::default(),
+					fly_in: false,
+					last_used: Some(Instant::now()),
+					mouse_scrolling: None,
+					held_keys: FxHashSet::default(),
+					keys: Vec::new(),
+					fullscreen: None,
+				};
+				if let Some(preferences) = &preferences {
+					toplevel.apply_preferences(preferences);
+					if !app_already_open {
+						toplevel.pose = preferences.pose;
+					}
+				}
+				let strategy = toplevel
+					.placement_strategy
+					.unwrap_or(state.placement.strategy);
+				if strategy != PlacementStrategy::Remembered {
+					toplevel.pose.take();
+				}
+				toplevel.placement = state.placement(strategy, toplevel.size_meters().into());
+				// panels that aren't world-locked remember where they were relative to the user
+				if toplevel.anchor != Anchor::World {
+					if let Some(offset) = toplevel.pose.take() {
+						toplevel.anchor_offset.replace(offset);
+						toplevel.placement = Placement::Body(offset);
+					}
+				}
+				toplevel.fly_in = state.placement.fly_in;
+				if let Some(parent_pose) = &parent_pose {
+					toplevel.pose.replace(dialog_pose(parent_pose));
+				}
+				state.toplevels.insert(item.id(), toplevel);
+				// so tiling puts it in the slot it was placed in
+				let tiling_index = (strategy == PlacementStrategy::TilingSlot)
+					.then(|| state.free_tiling_slot().0)
+					.and_then(|index| {
+						state
+							.tiling_order
+							.iter()
+							.filter(|uid| state.tileable(**uid))
+							.nth(index)
+							.and_then(|uid| state.tiling_order.iter().position(|t| t == uid))
+					});
+				match tiling_index {
+					Some(index) => state.tiling_order.insert(index, item.id()),
+					None => state.tiling_order.push(item.id()),
+				}
+				state.update_visibility();
+			})),
+			on_create_acceptor: FnWrapper(Box::new(|state, acceptor, field| {
+				state.acceptors.insert(acceptor.id(), (acceptor, field));
+				state.share_acceptors();
 			})),
-			on_create_acceptor: FnWrapper(Box::new(|_, _, _| {})),
 			on_capture_item: FnWrapper(Box::new(|state, panel_id, _| {
 				let Some(toplevel) = state.toplevels.get_mut(&panel_id) else {
 					return;
 				};
-				toplevel.enabled = false;
+				toplevel.captured = true;
+				state.update_visibility();
 			})),
 			on_release_item: FnWrapper(Box::new(|state, panel_id, _| {
 				let Some(toplevel) = state.toplevels.get_mut(&panel_id) else {
 					return;
 				};
-				toplevel.enabled = true;
+				toplevel.captured = false;
+				state.update_visibility();
 			})),
 			on_destroy_item: FnWrapper(Box::new(|state, id| {
+				// dialogs don't outlive their parent
+				for dialog in state.toplevels.keys() {
+					if state.parent_of(*dialog) == Some(id) {
+						let _ = state.toplevels[dialog].panel_item.close_toplevel();
+					}
+				}
 				state.toplevels.remove(&id);
+				state.tiling_order.retain(|uid| *uid != id);
+				state.keys.forget(id);
+			})),
+			on_destroy_acceptor: FnWrapper(Box::new(|state, id| {
+				state.acceptors.remove(&id);
+				state.share_acceptors();
 			})),
-			on_destroy_acceptor: FnWrapper(Box::new(|_, _| {})),
 		}
 		.build()
+		.child(
+			FrameHook::new(|state: &mut Self, _| {
+				state.move_workspaces();
+				state.update_visibility();
+				state.arrange_dialogs();
+				state.arrange_tiles();
+				state.update_focus();
+				state.route_keys();
+				state.stop_mouse_scrolls();
+				state.share_snap_targets();
+				state.sync_preferences();
+			})
+			.build(),
+		)
+		.child(
+			Dock { pose: dock_pose() }
+				.build()
+				.child(workspace_bar(self))
+				.child(tray(self)),
+		)
+		.maybe_child(
+			(self.focus_follows_gaze || self.tiling.enabled)
+				.then(|| GazeTracker::new(|state: &mut Self, head| state.track_head(head)).build()),
+		)
+		.maybe_child(self.overview.then(|| {
+			Dock {
+				pose: overview_pose(),
+			}
+			.build()
+			.child(overview(self))
+		}))
 		.stable_children(self.toplevels.iter().filter_map(|(uid, t)| {
 			let uid = *uid;
 			// self.toplevels.get_mut(&uid)?;
-			if !t.enabled {
+			if !self.stack_enabled(uid) {
 				return None;
 			}
 			Some((
@@ -197,7 +1001,19 @@ pub struct ChildState {
 
 #[derive(Debug)]
 pub struct ToplevelState {
+	/// shown, i.e. on the current workspace and not captured
 	enabled: bool,
+	/// held by a panel shell or the like
+	captured: bool,
+	workspace: String,
+	/// asked from the toolbar to go to the next workspace
+	move_workspace: bool,
+	/// hidden away in the tray, keeping its pose and size for when it comes back
+	minimized: bool,
+	/// picked from the overview
+	highlighted: bool,
+	/// gets typed into
+	focused: bool,
 	panel_item: PanelItem,
 	info: ToplevelInfo,
 	/// in px
@@ -206,6 +1022,67 @@ pub struct ToplevelState {
 	children: Vec<ChildState>,
 	density: f32, //pixels per meter
 	mouse_scroll_multiplier: f32,
+	/// last known pose relative to the client root
+	pose: Option<PanelPose>,
+	pose_request: Option<PoseRequest>,
+	/// a move or resize the client asked for that's in progress
+	client_drag: Option<ClientDrag>,
+	/// panel shells and such this toplevel can be thrown into
+	acceptors: Acceptors,
+	/// where the parent was when this dialog was last placed against it
+	parent_pose: Option<PanelPose>,
+	preferences_loaded: bool,
+	fullscreen_settings: FullscreenSettings,
+	snap_settings: SnapSettings,
+	upright: UprightSettings,
+	/// the other toplevels, to line up with
+	snap_targets: Vec<SnapTarget>,
+	/// laid out by tiling instead of placed by hand
+	tiled: bool,
+	/// where a tiled toplevel was just let go of, to swap with whatever it was dropped on
+	released_pose: Option<PanelPose>,
+	edge_handles: bool,
+	pointer_filter: PointerFilterSettings,
+	hand_gestures: HandGestureSettings,
+	/// keep the width to height ratio when resizing, toggled from the toolbar
+	aspect_lock: bool,
+	/// the resize handles change the density instead of the size in pixels, toggled from the toolbar
+	scale_mode: bool,
+	/// bend the panel around the user, toggled from the toolbar
+	curve_radius: Option<f32>,
+	/// what the panel stays put relative to, toggled from the toolbar
+	anchor: Anchor,
+	/// where the panel last sat relative to its anchor, which is what's remembered when it isn't world-locked
+	anchor_offset: Option<PanelPose>,
+	/// whether touches reach the app as touches or as the mouse, toggled from the toolbar
+	touch_mode: TouchMode,
+	/// what pinching two fingers does when touches act as the mouse, toggled from the toolbar
+	pinch_zoom: PinchZoom,
+	/// pinching left over from the last ctrl+wheel click
+	zoom_steps: ZoomSteps,
+	/// the keymap of the last key typed into the panel, to hold ctrl with for zooming
+	keymap_id: Option<u64>,
+	/// how this app's windows are placed, if not the usual way
+	placement_strategy: Option<PlacementStrategy>,
+	/// where the panel goes when it first shows up without a pose
+	placement: Placement,
+	fly_in: bool,
+	/// last clicked, touched or let go of
+	last_used: Option<Instant>,
+	/// the surface smooth scrolling from a mouse or touchpad last went to and when, until it's stopped
+	mouse_scrolling: Option<(SurfaceId, Instant)>,
+	/// keys pressed into this panel, whose release has to reach it too
+	held_keys: FxHashSet<u32>,
+	/// keys that reached this panel since the last frame, for `State` to send on to the right one
+	keys: Vec<Key>,
+	/// what to go back to once fullscreen ends
+	fullscreen: Option<FullscreenRestore>,
+}
+#[derive(Debug, Clone, Copy)]
+pub struct FullscreenRestore {
+	size: Vector2<u32>,
+	density: f32,
+	pose: Option<PanelPose>,
 }
 impl ToplevelState {
 	#[inline]
@@ -216,21 +1093,179 @@ impl ToplevelState {
 		]
 		.into()
 	}
-}
-impl Reify for ToplevelState {
-	fn reify(
-		&self,
-		_context: &Context,
-		_tasks: impl Tasker<Self>,
-	) -> impl stardust_xr_asteroids::Element<Self> {
-		let panel_thickness = 0.01;
-
+	/// Whether resizing keeps the current aspect ratio, either because the user asked for it
+	/// or because the app can't take any other.
+	pub fn aspect_locked(&self) -> bool {
+		let (min, max) = self.size_limits();
+		let fixed_ratio = min.y > 0.0
+			&& max.is_finite()
+			&& max.y > 0.0
+			&& ((min.x / min.y) / (max.x / max.y) - 1.0).abs() < ASPECT_TOLERANCE;
+		self.aspect_lock || fixed_ratio || self.fullscreen.is_some()
+	}
+	/// The smallest and largest size the app can take, in px,
+	/// with infinity along an axis it doesn't limit (which apps say with a max of 0).
+	pub fn size_limits(&self) -> (Vec2, Vec2) {
+		let min = self
+			.info
+			.min_size
+			.map(|s| vec2(s.x, s.y))
+			.unwrap_or_default()
+			.max(Vec2::ZERO);
+		let max = self.info.max_size.map_or(Vec2::INFINITY, |s| {
+			let unlimited = |max: f32| if max > 0.0 { max } else { f32::INFINITY };
+			vec2(unlimited(s.x), unlimited(s.y))
+		});
+		(min, max)
+	}
+	/// Radius the panel is actually bent to, if it's curved.
+	pub fn curve(&self) -> Option<f32> {
+		self.curve_radius
+			.map(|radius| curved_panel::clamp_radius(radius, self.size_meters().x))
+	}
+	/// Where a point in flat panel coordinates ends up, and which way the panel faces there.
+	fn on_panel(&self, point: Vec3) -> (Vec3, Quat) {
+		match self.curve() {
+			Some(radius) => (
+				curved_panel::bend(point, radius),
+				curved_panel::surface_rotation(point.x, radius),
+			),
+			None => (point, Quat::IDENTITY),
+		}
+	}
+	/// Glide over in front of the user at reading distance, facing them,
+	/// `offset` meters around them to the right.
+	fn summon(&mut self, offset: f32) {
+		let rotation = Quat::from_rotation_y(-offset / SUMMON_DISTANCE);
+		self.pose_request.replace(
+			PoseRequest::new(
+				PoseAnchor::Body,
+				PanelPose::new(rotation * Vec3::NEG_Z * SUMMON_DISTANCE, rotation),
+			)
+			.animated(),
+		);
+	}
+	/// Type a key routed here by `State`.
+	fn send_key(&mut self, key: Key) {
+		self.keymap_id.replace(key.keymap_id);
+		if key.pressed {
+			self.held_keys.insert(key.key);
+		} else {
+			self.held_keys.remove(&key.key);
+		}
+		let _ = self
+			.panel_item
+			.keyboard_key(key.surface, key.keymap_id, key.key, key.pressed);
+	}
+	/// Move the cursor to `pos`, in meters from the top left corner of the surface.
+	fn move_cursor(&mut self, surface_id: SurfaceId, pos: Vec2) {
+		let pos = pos * self.density;
+		self.cursor_pos = [
+			pos.x.clamp(0.0, self.info.size.x as f32),
+			pos.y.clamp(0.0, self.info.size.y as f32),
+		]
+		.into();
+		let _ = self
+			.panel_item
+			.absolute_pointer_motion(surface_id, self.cursor_pos);
+	}
+	/// Act out a touch gesture with the mouse, for apps without touch support.
+	fn touch_gesture(&mut self, surface_id: SurfaceId, gesture: TouchGesture) {
+		match gesture {
+			TouchGesture::Motion(pos) => self.move_cursor(surface_id, pos),
+			TouchGesture::Button { button, pressed } => {
+				if pressed {
+					self.last_used.replace(Instant::now());
+				}
+				let _ = self.panel_item.pointer_button(surface_id, button, pressed);
+			}
+			// the content follows the fingers, and gestures are +y down
+			TouchGesture::Scroll(moved) => self.scroll(
+				surface_id,
+				ScrollEvent::Continuous(vec2(-moved.x, moved.y) * self.density),
+			),
+			TouchGesture::ScrollStop => self.scroll(surface_id, ScrollEvent::Stop),
+			TouchGesture::Zoom(factor) => match (self.pinch_zoom, self.keymap_id) {
+				(PinchZoom::CtrlScroll, Some(keymap_id)) => {
+					let steps = self.zoom_steps.add(factor);
+					if steps != 0 {
+						self.ctrl_scroll(surface_id, keymap_id, steps as f32);
+					}
+				}
+				// ctrl can only be held with a keymap from a keyboard, so until the app's been typed into
+				// scale the whole window like the scale handles do instead, except for tiles and
+				// the virtual screen which have their own size
+				_ => {
+					if !self.tiled && self.fullscreen.is_none() {
+						self.density =
+							(self.density / factor).clamp(MIN_ZOOM_DENSITY, MAX_ZOOM_DENSITY);
+					}
+				}
+			},
+		}
+	}
+	/// Turn the mouse wheel `steps` clicks up while holding ctrl, which most apps zoom in with.
+	fn ctrl_scroll(&mut self, surface_id: SurfaceId, keymap_id: u64, steps: f32) {
+		let ctrl = input_event_codes::KEY_LEFTCTRL!();
+		let _ = self
+			.panel_item
+			.keyboard_key(surface_id, keymap_id, ctrl, true);
+		let wheel = ScrollEvent::Wheel {
+			steps: vec2(0.0, steps),
+			distance: Vec2::ZERO,
+		};
+		if let Some((distance, steps)) = wheel.for_app(1.0) {
+			let _ = self.panel_item.pointer_scroll(surface_id, distance, steps);
+		}
+		// a ctrl held on the keyboard stays held
+		if !self.held_keys.contains(&ctrl) {
+			let _ = self
+				.panel_item
+				.keyboard_key(surface_id, keymap_id, ctrl, false);
+		}
+	}
+	/// Let the app know smooth scrolling from the mouse or touchpad stopped.
+	fn stop_mouse_scroll(&mut self) {
+		if let Some((surface_id, _)) = self.mouse_scrolling.take() {
+			self.scroll(surface_id, ScrollEvent::Stop);
+		}
+	}
+	/// Pass scrolling on to the app, flipping y to go down like it expects.
+	fn scroll(&self, surface_id: SurfaceId, scroll: ScrollEvent) {
+		let _ = match scroll.for_app(self.mouse_scroll_multiplier) {
+			Some((distance, steps)) => self.panel_item.pointer_scroll(surface_id, distance, steps),
+			None => self.panel_item.pointer_stop_scroll(surface_id),
+		};
+	}
+	/// Stand the panel straight up, turning it around its center.
+	fn level(&mut self) {
+		let Some(pose) = self.pose else {
+			return;
+		};
+		let settings = UprightSettings {
+			mode: Upright::YawOnly,
+			..self.upright
+		};
+		self.pose_request
+			.replace(PoseRequest::new(PoseAnchor::Stage, upright(&settings, pose)).animated());
+	}
+	/// Resize to fill `size` in meters as far as the app allows, keeping the density.
+	fn fit_to(&mut self, size: Vec2) {
+		let (min, max) = self.size_limits();
+		let size = (size * self.density).max(min).min(max);
+		let size = [size.x as u32, size.y as u32];
+		if size != [self.info.size.x, self.info.size.y] {
+			self.set_size(size.into());
+		}
+	}
+	/// The title and the last part of the app_id, whichever are known.
+	pub fn title_text(&self) -> String {
 		let app_name = self
 			.info
 			.app_id
 			.as_ref()
 			.map(|id| id.split('.').next_back().unwrap_or_default());
-		let title_text = match (&self.info.title, app_name) {
+		match (&self.info.title, app_name) {
 			(Some(title), Some(app_name)) => {
 				if title == app_name {
 					title.to_string()
@@ -241,7 +1276,155 @@ impl Reify for ToplevelState {
 			(Some(title), None) => title.to_string(),
 			(None, Some(app_name)) => app_name.to_string(),
 			(None, None) => String::new(),
+		}
+	}
+	pub fn preferences(&self) -> ToplevelPreferences {
+		// don't remember the virtual screen as the app's usual size and place
+		let (size, density, pose) = match &self.fullscreen {
+			Some(restore) => (restore.size, restore.density, restore.pose),
+			None => (self.info.size, self.density, self.pose),
+		};
+		// following the user around isn't a new place to remember
+		let pose = match self.anchor {
+			Anchor::World => pose,
+			Anchor::Body | Anchor::Head => self.anchor_offset,
 		};
+		ToplevelPreferences {
+			density,
+			size: Some([size.x, size.y]),
+			pose,
+			scroll_multiplier: self.mouse_scroll_multiplier,
+			aspect_lock: self.aspect_lock,
+			curve_radius: self.curve_radius,
+			workspace: Some(self.workspace.clone()),
+			placement: self.placement_strategy,
+			anchor: self.anchor,
+			touch_mode: self.touch_mode,
+			pinch_zoom: self.pinch_zoom,
+		}
+	}
+	fn set_size(&mut self, size: Vector2<u32>) {
+		let _ = self.panel_item.set_toplevel_size([size.x, size.y]);
+		self.info.size = size;
+	}
+	pub fn set_fullscreen(&mut self, active: bool) {
+		if active == self.fullscreen.is_some() {
+			return;
+		}
+		if active {
+			self.fullscreen.replace(FullscreenRestore {
+				size: self.info.size,
+				density: self.density,
+				pose: self.pose,
+			});
+			let settings = self.fullscreen_settings;
+			self.density = settings.resolution[0] as f32 / settings.width;
+			self.set_size(settings.resolution.into());
+			self.pose_request.replace(PoseRequest::new(
+				PoseAnchor::Body,
+				PanelPose::new(vec3(0.0, 0.0, -settings.distance), Quat::IDENTITY),
+			));
+		} else if let Some(restore) = self.fullscreen.take() {
+			self.density = restore.density;
+			self.set_size(restore.size);
+			if let Some(pose) = restore.pose {
+				self.pose_request
+					.replace(PoseRequest::new(PoseAnchor::Stage, pose));
+			}
+		}
+	}
+	/// Column of buttons along the left edge of the panel.
+	fn toolbar(&self) -> impl Element<Self> {
+		let buttons: Vec<ToolbarButton> = vec![
+			("aspect", self.aspect_lock, |state| {
+				state.aspect_lock = !state.aspect_lock
+			}),
+			("scale", self.scale_mode, |state| {
+				state.scale_mode = !state.scale_mode
+			}),
+			("curve", self.curve_radius.is_some(), |state| {
+				state.curve_radius = match state.curve_radius {
+					Some(_) => None,
+					None => Some(DEFAULT_CURVE_RADIUS),
+				}
+			}),
+			("move", false, |state| state.move_workspace = true),
+			("min", false, |state| state.minimized = true),
+			("level", false, |state| state.level()),
+			(self.anchor.label(), self.anchor != Anchor::World, |state| {
+				state.anchor = state.anchor.next()
+			}),
+			(
+				self.touch_mode.label(),
+				self.touch_mode != TouchMode::Touch,
+				|state| state.touch_mode = state.touch_mode.next(),
+			),
+			(
+				self.pinch_zoom.label(),
+				self.pinch_zoom != PinchZoom::CtrlScroll,
+				|state| state.pinch_zoom = state.pinch_zoom.next(),
+			),
+			("here", false, |state| {
+				if !state.tiled {
+					state.summon(0.0)
+				}
+			}),
+		];
+		let (pos, rot) = self.on_panel(vec3(
+			self.size_meters().x / -2.0 - TOOLBAR_BUTTON_SIZE[0] / 2.0 - 0.025,
+			self.size_meters().y / 2.0 - TOOLBAR_BUTTON_SIZE[1] / 2.0,
+			0.0,
+		));
+		Spatial::default()
+			.pos(pos)
+			.rot(rot)
+			.build()
+			.children(
+				buttons
+					.into_iter()
+					.enumerate()
+					.map(|(i, (label, active, on_press))| {
+						ActionButton::new(TOOLBAR_BUTTON_SIZE, on_press)
+							.active(active)
+							.pos([0.0, i as f32 * -(TOOLBAR_BUTTON_SIZE[1] + 0.005), 0.0])
+							.build()
+							.child(
+								Text::new(label)
+									.character_height(TOOLBAR_BUTTON_SIZE[1] * 0.5)
+									.align_x(XAlign::Center)
+									.align_y(YAlign::Center)
+									.pos([0.0, 0.0, 0.001])
+									.build(),
+							)
+					}),
+			)
+	}
+	/// Everything but the pose, since a panel that's already placed has to be moved there instead.
+	pub fn apply_preferences(&mut self, preferences: &ToplevelPreferences) {
+		self.density = preferences.density;
+		self.mouse_scroll_multiplier = preferences.scroll_multiplier;
+		self.aspect_lock = preferences.aspect_lock;
+		self.curve_radius = preferences.curve_radius;
+		self.placement_strategy = preferences.placement;
+		self.anchor = preferences.anchor;
+		self.touch_mode = preferences.touch_mode;
+		self.pinch_zoom = preferences.pinch_zoom;
+		if let Some(workspace) = &preferences.workspace {
+			self.workspace.clone_from(workspace);
+		}
+		if let Some(size) = preferences.size {
+			self.set_size(size.into());
+		}
+	}
+}
+impl Reify for ToplevelState {
+	fn reify(
+		&self,
+		_context: &Context,
+		_tasks: impl Tasker<Self>,
+	) -> impl stardust_xr_asteroids::Element<Self> {
+		let panel_thickness = 0.01;
+		let title_text = self.title_text();
 
 		InitialPositioner(
 			self.panel_item
@@ -252,7 +1435,13 @@ impl Reify for ToplevelState {
 		)
 		.build()
 		.child(
-			InitialPanelPlacement
+			InitialPanelPlacement::new(self.pose)
+				.placement(self.placement)
+				.fly_in(self.fly_in)
+				.upright(self.upright)
+				.on_placed(|state: &mut Self, pose| {
+					state.pose.replace(pose);
+				})
 				.build()
 				.child(
 					PanelWrapper::<Self>::new(self.panel_item.clone())
@@ -265,6 +1454,30 @@ impl Reify for ToplevelState {
 						.on_toplevel_title_changed(|state, title| {
 							state.info.title.replace(title);
 						})
+						.on_toplevel_parent_changed(|state, parent_id| {
+							if state.info.parent != Some(parent_id) {
+								state.info.parent.replace(parent_id);
+								state.parent_pose.take();
+							}
+						})
+						.on_toplevel_fullscreen_active(|state, active| {
+							state.set_fullscreen(active);
+						})
+						.on_toplevel_move_request(|state| {
+							if state.fullscreen.is_none() {
+								state.client_drag.replace(ClientDrag::Move);
+							}
+						})
+						.on_toplevel_resize_request(|state, up, down, left, right| {
+							if state.fullscreen.is_none() {
+								state.client_drag.replace(ClientDrag::Resize {
+									up,
+									down,
+									left,
+									right,
+								});
+							}
+						})
 						.on_set_cursor(|state, geometry| {
 							state.cursor.replace(geometry);
 						})
@@ -283,15 +1496,35 @@ impl Reify for ToplevelState {
 				.child(
 					ResizeHandles::<ToplevelState> {
 						reparentable: true,
+						enabled: self.fullscreen.is_none(),
+						edge_handles: self.edge_handles,
+						pose_request: self.pose_request,
+						client_drag: self.client_drag,
 						current_size: self.size_meters(),
-						min_size: self
-							.info
-							.min_size
-							.map(|s| [s.x / self.density, s.y / self.density].into()),
-						max_size: self
-							.info
-							.max_size
-							.map(|s| [s.x / self.density, s.y / self.density].into()),
+						min_size: Some((self.size_limits().0 / self.density).into()),
+						max_size: Some((self.size_limits().1 / self.density).into()),
+						aspect_lock: self.aspect_locked(),
+						scale_mode: self.scale_mode,
+						curve_radius: self.curve(),
+						// tiles go back to their slot instead
+						snap: if self.tiled {
+							SnapSettings {
+								neighbors: false,
+								grid: false,
+								level: false,
+								..self.snap_settings
+							}
+						} else {
+							self.snap_settings
+						},
+						snap_targets: self.snap_targets.clone(),
+						upright: self.upright,
+						// tiles and the virtual screen have their own place
+						anchor: if self.tiled || self.fullscreen.is_some() {
+							Anchor::World
+						} else {
+							self.anchor
+						},
 						on_size_changed: FnWrapper(Box::new(|state, size_meters| {
 							let size = [
 								(size_meters.x * state.density) as u32,
@@ -302,26 +1535,75 @@ impl Reify for ToplevelState {
 							state.cursor_pos.x = state.cursor_pos.x.clamp(0.0, size[0] as f32);
 							state.cursor_pos.y = state.cursor_pos.y.clamp(0.0, size[1] as f32);
 						})),
+						on_scaled: FnWrapper(Box::new(|state, size_meters| {
+							if size_meters.x > 0.0 {
+								state.density = state.info.size.x as f32 / size_meters.x;
+							}
+						})),
+						on_pose_changed: FnWrapper(Box::new(|state, pose| {
+							state.pose.replace(pose);
+						})),
+						on_anchor_offset_changed: FnWrapper(Box::new(|state, offset| {
+							state.anchor_offset.replace(offset);
+						})),
+						on_client_drag_finished: FnWrapper(Box::new(|state| {
+							state.client_drag.take();
+						})),
+						on_released: FnWrapper(Box::new(|state, pose| {
+							state.highlighted = false;
+							state.last_used.replace(Instant::now());
+							if state.tiled {
+								state.released_pose.replace(pose);
+							}
+						})),
 					}
 					.build()
-					.child(
+					.maybe_child(self.fullscreen.is_none().then(|| {
 						// Close button
+						let (pos, rot) = self.on_panel(vec3(
+							self.size_meters().x / 2.0,
+							self.size_meters().y / -2.0,
+							panel_thickness / 2.0,
+						));
 						ExposureButton::<Self> {
-							transform: Transform::from_translation([
-								self.size_meters().x / 2.0,
-								self.size_meters().y / -2.0,
-								panel_thickness / 2.0,
-							]),
+							transform: Transform::from_translation_rotation(pos, rot),
 							thickness: panel_thickness,
 							gain: 2.0,
 							on_click: FnWrapper(Box::new(|state: &mut Self| {
 								let _ = state.panel_item.close_toplevel();
 							})),
 						}
-						.build(),
-					)
-					.child(
+						.build()
+					}))
+					.maybe_child(self.fullscreen.is_none().then(|| self.toolbar()))
+					.maybe_child((self.highlighted || self.focused).then(|| {
+						Highlight {
+							transform: Transform::identity(),
+							size: self.size_meters(),
+							thickness: if self.highlighted { 0.004 } else { 0.0015 },
+						}
+						.build()
+					}))
+					.maybe_child(self.fullscreen.is_none().then(|| {
+						// Panel shell transfer
+						PanelShellTransferBall {
+							transform: Transform::from_translation([
+								0.0,
+								self.size_meters().y / -2.0,
+								0.0,
+							]),
+							panel_item: self.panel_item.clone(),
+							acceptors: self.acceptors.clone(),
+						}
+						.build()
+					}))
+					.maybe_child(self.fullscreen.is_none().then(|| {
 						// Side text
+						let (pos, rot) = self.on_panel(vec3(
+							self.size_meters().x / 2.0 + 0.0005,
+							self.size_meters().y / 2.0 - 0.001,
+							panel_thickness / 2.0,
+						));
 						Text::new(title_text)
 							.character_height(panel_thickness * 0.75)
 							.align_x(XAlign::Left)
@@ -332,17 +1614,13 @@ impl Reify for ToplevelState {
 								anchor_align_x: XAlign::Left,
 								anchor_align_y: YAlign::Bottom,
 							})
-							.pos([
-								self.size_meters().x / 2.0 + 0.0005,
-								self.size_meters().y / 2.0 - 0.001,
-								panel_thickness / 2.0,
-							])
+							.pos(pos)
 							.rot(
-								Quat::from_rotation_z(-FRAC_PI_2)
+								rot * Quat::from_rotation_z(-FRAC_PI_2)
 									* Quat::from_rotation_x(-FRAC_PI_2),
 							)
-							.build(),
-					)
+							.build()
+					}))
 					.child(reify_surface(
 						&self.panel_item,
 						SurfaceId::Toplevel(()),
@@ -355,6 +1633,11 @@ impl Reify for ToplevelState {
 						0,
 						panel_thickness,
 						self.density,
+						self.curve(),
+						None,
+						self.touch_mode,
+						&self.pointer_filter,
+						self.hand_gestures,
 						self.children
 							.iter()
 							.map(|child| {
@@ -365,6 +1648,10 @@ impl Reify for ToplevelState {
 										&self.panel_item,
 										panel_thickness,
 										self.density,
+										self.curve(),
+										self.touch_mode,
+										&self.pointer_filter,
+										self.hand_gestures,
 									),
 								)
 							})
@@ -384,13 +1671,15 @@ impl Reify for ToplevelState {
 							let pos_px = cursor_pos - panel_size_px_half + geometry_size_half
 								- geometry_origin;
 							let pos_m = pos_px * vec2(1.0, -1.0) / self.density;
+							let (pos, rot) = self.on_panel(pos_m.extend(0.001));
 
 							Model::namespaced(State::APP_ID, "panel")
 								.part(
 									ModelPart::new("Panel")
 										.apply_panel_item_cursor(self.panel_item.clone()),
 								)
-								.pos([pos_m.x, pos_m.y, 0.001])
+								.pos(pos)
+								.rot(rot)
 								.scl([
 									geometry.size.x as f32 / self.density,
 									geometry.size.y as f32 / self.density,
@@ -404,12 +1693,17 @@ impl Reify for ToplevelState {
 	}
 }
 impl ChildState {
+	#[allow(clippy::too_many_arguments)]
 	fn reify(
 		&self,
 		parent_size: Vector2<u32>,
 		panel_item: &PanelItem,
 		panel_thickness: f32,
 		density: f32,
+		parent_curve_radius: Option<f32>,
+		touch_mode: TouchMode,
+		pointer_filter: &PointerFilterSettings,
+		hand_gestures: HandGestureSettings,
 	) -> impl Element<ToplevelState> {
 		reify_surface(
 			panel_item,
@@ -420,6 +1714,11 @@ impl ChildState {
 			1,
 			panel_thickness,
 			density,
+			None,
+			parent_curve_radius,
+			touch_mode,
+			pointer_filter,
+			hand_gestures,
 			self.children
 				.iter()
 				.map(|child| {
@@ -430,6 +1729,10 @@ impl ChildState {
 							panel_item,
 							panel_thickness,
 							density,
+							None,
+							touch_mode,
+							pointer_filter,
+							hand_gestures,
 						),
 					)
 				})
@@ -449,6 +1752,11 @@ fn reify_surface<E: Element<ToplevelState>>(
 	z_offset: i32,
 	thickness: f32,
 	density: f32,
+	curve_radius: Option<f32>,
+	parent_curve_radius: Option<f32>,
+	touch_mode: TouchMode,
+	pointer_filter: &PointerFilterSettings,
+	hand_gestures: HandGestureSettings,
 	children: FxHashMap<u64, E>,
 ) -> impl Element<ToplevelState> {
 	let parent_size = parent_size.into();
@@ -465,12 +1773,24 @@ fn reify_surface<E: Element<ToplevelState>>(
 		geometry.size.y as f32 / density,
 	);
 
-	let shape = Shape::Box([size_meters.x, size_meters.y, thickness].into());
+	let center = (origin_meters - parent_origin_meters + (size_meters / vec2(2.0, -2.0)))
+		.extend(thickness * (z_offset as f32));
+	// popups stay flat against a curved parent, far enough out for their edges not to sink in
+	let (pos, rot) = match parent_curve_radius {
+		Some(radius) => {
+			let sag = radius * (1.0 - (size_meters.x / radius / 2.0).cos());
+			(
+				curved_panel::bend(center + vec3(0.0, 0.0, sag), radius),
+				curved_panel::surface_rotation(center.x, radius),
+			)
+		}
+		None => (center, Quat::IDENTITY),
+	};
+
+	let shape = curved_panel::field_shape(size_meters, thickness, curve_radius);
 	Spatial::default()
-		.pos(
-			(origin_meters - parent_origin_meters + (size_meters / vec2(2.0, -2.0)))
-				.extend(thickness * (z_offset as f32)),
-		)
+		.pos(pos)
+		.rot(rot)
 		.build()
 		.child(
 			Derezzable::<ToplevelState>::new(
@@ -481,7 +1801,18 @@ fn reify_surface<E: Element<ToplevelState>>(
 			)
 			.build(),
 		)
-		.child(
+		.maybe_child(curve_radius.map(|radius| {
+			CurvedPanelModel {
+				transform: Transform::identity(),
+				panel_item: panel_item.clone(),
+				surface_id,
+				size: size_meters.into(),
+				thickness,
+				radius,
+			}
+			.build()
+		}))
+		.maybe_child(curve_radius.is_none().then(|| {
 			Model::namespaced(State::APP_ID, "panel")
 				.part(ModelPart::new("Panel").apply_panel_item(panel_item.clone(), surface_id))
 				.scl([
@@ -489,20 +1820,21 @@ fn reify_surface<E: Element<ToplevelState>>(
 					geometry.size.y as f32 / density,
 					thickness,
 				])
-				.build(),
-		)
+				.build()
+		}))
 		// inputs
 		.maybe_child(input.then(move || {
 			Spatial::default()
 				.build()
 				.child(
 					KeyboardHandler::<ToplevelState>::new(shape.clone(), move |state, key_data| {
-						let _ = state.panel_item.keyboard_key(
-							surface_id,
-							key_data.keymap_id,
-							key_data.key,
-							key_data.pressed,
-						);
+						// `State` sends it on to the focused panel, once for all the panels it reached
+						state.keys.push(Key {
+							surface: surface_id,
+							keymap_id: key_data.keymap_id,
+							key: key_data.key,
+							pressed: key_data.pressed,
+						});
 					})
 					.build(),
 				)
@@ -510,6 +1842,9 @@ fn reify_surface<E: Element<ToplevelState>>(
 					MouseHandler::<ToplevelState>::new(
 						shape,
 						move |state, button, pressed| {
+							if pressed {
+								state.last_used.replace(Instant::now());
+							}
 							let _ = state.panel_item.pointer_button(surface_id, button, pressed);
 						},
 						move |state, motion| {
@@ -526,91 +1861,55 @@ fn reify_surface<E: Element<ToplevelState>>(
 								.panel_item
 								.absolute_pointer_motion(surface_id, state.cursor_pos);
 						},
-						move |state, scroll_discrete| {
-							let _ = state.panel_item.pointer_scroll(
+						move |state, steps| {
+							state.scroll(
 								surface_id,
-								[0.0; 2],
-								[
-									scroll_discrete.x * state.mouse_scroll_multiplier,
-									-scroll_discrete.y * state.mouse_scroll_multiplier,
-								],
+								ScrollEvent::Wheel {
+									steps: vec2(steps.x, steps.y),
+									distance: Vec2::ZERO,
+								},
 							);
 						},
-						move |state, scroll_continuous| {
-							// TODO: fix the server, we're not sending some events some apps need to register
-							// continuous scroll, we should send that instead of discrete
-							let _ = state.panel_item.pointer_scroll(
-								surface_id,
-								[0.0; 2],
-								[
-									scroll_continuous.x * state.mouse_scroll_multiplier,
-									-scroll_continuous.y * state.mouse_scroll_multiplier,
-								],
-							);
+						move |state, distance| {
+							let distance = vec2(distance.x, distance.y);
+							if distance == Vec2::ZERO {
+								state.stop_mouse_scroll();
+								return;
+							}
+							state.scroll(surface_id, ScrollEvent::Continuous(distance));
+							state.mouse_scrolling.replace((surface_id, Instant::now()));
 						},
 					)
 					.build(),
 				)
 				.child(
 					PointerPlane::<ToplevelState>::default()
+						.curve_radius(curve_radius)
 						.physical_size([size_meters.x, size_meters.y])
 						.thickness(thickness)
+						.density(density)
+						.filter(pointer_filter.clone())
+						.hand_gestures(hand_gestures)
 						.on_mouse_button(move |state, button, pressed| {
+							if pressed {
+								state.last_used.replace(Instant::now());
+							}
 							let _ = state.panel_item.pointer_button(surface_id, button, pressed);
 						})
 						.on_pointer_motion(move |state, pos| {
-							let pixel_pos = [pos.x * state.density, pos.y * state.density];
-							state.cursor_pos = pixel_pos.into();
-							state.cursor_pos.x =
-								state.cursor_pos.x.clamp(0.0, state.info.size.x as f32);
-							state.cursor_pos.y =
-								state.cursor_pos.y.clamp(0.0, state.info.size.y as f32);
-							let _ = state
-								.panel_item
-								.absolute_pointer_motion(surface_id, state.cursor_pos);
-						})
-						.on_scroll(move |state, scroll| {
-							let _ = match (scroll.scroll_continuous, scroll.scroll_discrete) {
-								(None, None) => state.panel_item.pointer_stop_scroll(surface_id),
-								(None, Some(steps)) => state.panel_item.pointer_scroll(
-									surface_id,
-									[0.0; 2],
-									[
-										steps.x * state.mouse_scroll_multiplier,
-										-steps.y * state.mouse_scroll_multiplier,
-									],
-								),
-								(Some(continuous), None) => state.panel_item.pointer_scroll(
-									surface_id,
-									// TODO: fix the server, we're not sending some events some apps need to register
-									// continuous scroll, we should send that instead of discrete
-									[0.0; 2],
-									[
-										continuous.x * state.mouse_scroll_multiplier,
-										-continuous.y * state.mouse_scroll_multiplier,
-									],
-								),
-								(Some(continuous), Some(steps)) => state.panel_item.pointer_scroll(
-									surface_id,
-									// TODO: fix the server, we're not sending some events some apps need to register
-									// continuous scroll, we should send that instead of discrete
-									[0.0; 2],
-									[
-										(steps.x * state.mouse_scroll_multiplier)
-											+ (continuous.x * state.mouse_scroll_multiplier),
-										(steps.y * state.mouse_scroll_multiplier)
-											+ (continuous.y * state.mouse_scroll_multiplier),
-									],
-								),
-							};
+							state.move_cursor(surface_id, vec2(pos.x, pos.y))
 						})
+						.on_scroll(move |state, scroll| state.scroll(surface_id, scroll))
 						.build(),
 				)
 				.child(
 					TouchPlane::<ToplevelState>::default()
+						.curve_radius(curve_radius)
 						.physical_size([size_meters.x, size_meters.y])
 						.thickness(thickness)
+						.gestures(touch_mode == TouchMode::Pointer)
 						.on_touch_down(move |state, id, position| {
+							state.last_used.replace(Instant::now());
 							let _ = state.panel_item.touch_down(
 								surface_id,
 								id,
@@ -626,6 +1925,7 @@ fn reify_surface<E: Element<ToplevelState>>(
 						.on_touch_up(|state, id| {
 							let _ = state.panel_item.touch_up(id);
 						})
+						.on_gesture(move |state, gesture| state.touch_gesture(surface_id, gesture))
 						.build(),
 				)
 		}))
@@ -0,0 +1,152 @@
+use crate::{action_button::ActionButton, panel_pose::PanelPose, State, TOOLBAR_BUTTON_SIZE};
+use glam::{vec2, vec3, Quat, Vec2, Vec3};
+use stardust_xr_asteroids::{
+	elements::{Model, ModelPart, Spatial, Text},
+	ClientState, CustomElement, Element, Transformable as _,
+};
+use stardust_xr_fusion::{
+	drawable::{TextBounds, TextFit, XAlign, YAlign},
+	items::panel::SurfaceId,
+};
+
+/// Space each toplevel gets in the overview, in meters, including its title.
+const CELL_SIZE: Vec2 = vec2(0.24, 0.17);
+/// Height of the title above each thumbnail, in meters.
+const TITLE_HEIGHT: f32 = 0.02;
+const CELL_GAP: f32 = 0.02;
+
+/// Where the overview floats, relative to the user's body.
+pub fn overview_pose() -> PanelPose {
+	PanelPose::new(vec3(0.0, 0.0, -0.6), Quat::IDENTITY)
+}
+
+/// Where a toplevel is shown in the overview.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Thumbnail {
+	/// center of the cell, relative to the overview
+	pub pos: Vec3,
+	/// of the thumbnail itself, in meters
+	pub size: Vec2,
+}
+
+/// A grid of thumbnails for toplevels of `sizes`, filling rows first from the top left,
+/// each shrunk to fit its cell without changing its aspect ratio.
+pub fn layout(sizes: &[Vec2]) -> Vec<Thumbnail> {
+	if sizes.is_empty() {
+		return Vec::new();
+	}
+	let columns = (sizes.len() as f32).sqrt().ceil() as usize;
+	let rows = sizes.len().div_ceil(columns);
+	let step = CELL_SIZE + CELL_GAP;
+	let space = CELL_SIZE - vec2(0.0, TITLE_HEIGHT);
+	sizes
+		.iter()
+		.enumerate()
+		.map(|(i, size)| {
+			let (column, row) = (i % columns, i / columns);
+			let scale = (space / size.max(Vec2::splat(0.001))).min_element();
+			Thumbnail {
+				pos: vec3(
+					(column as f32 - (columns - 1) as f32 / 2.0) * step.x,
+					((rows - 1) as f32 / 2.0 - row as f32) * step.y,
+					0.0,
+				),
+				size: *size * scale,
+			}
+		})
+		.collect()
+}
+
+/// Live thumbnails of every toplevel except dialogs, in the order they were opened.
+/// Tapping one brings it in front of the user, "find" highlights it where it is.
+pub fn overview(state: &State) -> impl Element<State> {
+	let uids = state
+		.tiling_order
+		.iter()
+		.copied()
+		.filter(|uid| state.toplevels.contains_key(uid) && state.parent_of(*uid).is_none())
+		.collect::<Vec<_>>();
+	let sizes = uids
+		.iter()
+		.map(|uid| Vec2::from(state.toplevels[uid].size_meters()))
+		.collect::<Vec<_>>();
+	Spatial::default()
+		.build()
+		.stable_children(
+			uids.into_iter()
+				.zip(layout(&sizes))
+				.map(|(uid, thumbnail)| {
+					let toplevel = &state.toplevels[&uid];
+					let title_y = CELL_SIZE.y / 2.0 - TITLE_HEIGHT / 2.0;
+					let find_x = CELL_SIZE.x / 2.0 - TOOLBAR_BUTTON_SIZE[0] / 2.0;
+					(
+						uid,
+						Spatial::default()
+							.pos(thumbnail.pos)
+							.build()
+							.child(
+								Model::namespaced(State::APP_ID, "panel")
+									.part(ModelPart::new("Panel").apply_panel_item(
+										toplevel.panel_item.clone(),
+										SurfaceId::Toplevel(()),
+									))
+									.pos([0.0, -TITLE_HEIGHT / 2.0, 0.0])
+									.scl([thumbnail.size.x, thumbnail.size.y, 0.005])
+									.build(),
+							)
+							.child(
+								ActionButton::new(thumbnail.size, move |state: &mut State| {
+									state.show_toplevel(uid, true);
+								})
+								.pos([0.0, -TITLE_HEIGHT / 2.0, 0.004])
+								.build(),
+							)
+							.child(
+								Text::new(toplevel.title_text())
+									.character_height(TITLE_HEIGHT * 0.6)
+									.align_x(XAlign::Left)
+									.align_y(YAlign::Center)
+									.bounds(TextBounds {
+										bounds: [
+											CELL_SIZE.x - TOOLBAR_BUTTON_SIZE[0] - 0.005,
+											TITLE_HEIGHT,
+										]
+										.into(),
+										fit: TextFit::Squeeze,
+										anchor_align_x: XAlign::Left,
+										anchor_align_y: YAlign::Center,
+									})
+									.pos([-CELL_SIZE.x / 2.0, title_y, 0.0])
+									.build(),
+							)
+							.child(
+								ActionButton::new(TOOLBAR_BUTTON_SIZE, move |state: &mut State| {
+									state.show_toplevel(uid, false);
+								})
+								.pos([find_x, title_y, 0.0])
+								.build()
+								.child(
+									Text::new("find")
+										.character_height(TOOLBAR_BUTTON_SIZE[1] * 0.5)
+										.align_x(XAlign::Center)
+										.align_y(YAlign::Center)
+										.pos([0.0, 0.0, 0.001])
+										.build(),
+								),
+							),
+					)
+				}),
+		)
+}
+
+#[test]
+fn thumbnails_keep_aspect() {
+	let thumbnails = layout(&[vec2(1.6, 0.9), vec2(0.3, 0.6), vec2(0.5, 0.5)]);
+	assert_eq!(thumbnails.len(), 3);
+	assert!((thumbnails[0].size.x - CELL_SIZE.x).abs() < 0.0001);
+	assert!((thumbnails[0].size.x / thumbnails[0].size.y - 1.6 / 0.9).abs() < 0.0001);
+	assert!((thumbnails[1].size.y - (CELL_SIZE.y - TITLE_HEIGHT)).abs() < 0.0001);
+	// 2 columns, so the third starts the second row
+	assert!(thumbnails[2].pos.y < thumbnails[0].pos.y);
+	assert_eq!(thumbnails[2].pos.x, thumbnails[0].pos.x);
+}
@@ -0,0 +1,132 @@
+use glam::{Quat, Vec3};
+use serde::{Deserialize, Serialize};
+use stardust_xr_fusion::spatial::{SpatialAspect, SpatialRefAspect, Transform};
+use std::{
+	sync::atomic::{AtomicU64, Ordering},
+	time::{Duration, Instant},
+};
+
+/// How long animated moves take, in seconds.
+const ANIMATION_DURATION: f32 = 0.4;
+/// How often a panel is moved while animating.
+const ANIMATION_STEP: Duration = Duration::from_millis(11);
+
+/// Translation and rotation of a panel relative to the client root (the stage).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PanelPose {
+	pub translation: [f32; 3],
+	pub rotation: [f32; 4],
+}
+impl PanelPose {
+	pub fn new(translation: Vec3, rotation: Quat) -> Self {
+		PanelPose {
+			translation: translation.to_array(),
+			rotation: rotation.to_array(),
+		}
+	}
+	pub fn from_transform(transform: &Transform) -> Option<Self> {
+		Some(Self::new(
+			transform.translation?.into(),
+			transform.rotation?.into(),
+		))
+	}
+
+	#[inline]
+	pub fn translation(&self) -> Vec3 {
+		Vec3::from_array(self.translation)
+	}
+	#[inline]
+	pub fn rotation(&self) -> Quat {
+		Quat::from_array(self.rotation).normalize()
+	}
+	pub fn transform(&self) -> Transform {
+		Transform::from_translation_rotation(self.translation(), self.rotation())
+	}
+
+	/// `local` is relative to this pose, the result is relative to whatever this pose is relative to.
+	pub fn compose(&self, local: &PanelPose) -> PanelPose {
+		PanelPose::new(
+			self.translation() + self.rotation() * local.translation(),
+			self.rotation() * local.rotation(),
+		)
+	}
+	pub fn inverse(&self) -> PanelPose {
+		let rotation = self.rotation().inverse();
+		PanelPose::new(rotation * -self.translation(), rotation)
+	}
+	/// Part way from this pose to `other`, `t` going from 0 to 1.
+	pub fn lerp(&self, other: &PanelPose, t: f32) -> PanelPose {
+		PanelPose::new(
+			self.translation().lerp(other.translation(), t),
+			self.rotation().slerp(other.rotation(), t),
+		)
+	}
+}
+
+/// Move `spatial` from `start` to `end` relative to `root`, easing in and out,
+/// calling `on_step` with every pose along the way.
+pub async fn glide(
+	spatial: &impl SpatialAspect,
+	root: &impl SpatialRefAspect,
+	start: PanelPose,
+	end: PanelPose,
+	on_step: impl Fn(PanelPose),
+) {
+	let begin = Instant::now();
+	let mut interval = tokio::time::interval(ANIMATION_STEP);
+	loop {
+		interval.tick().await;
+		let t = (begin.elapsed().as_secs_f32() / ANIMATION_DURATION).min(1.0);
+		let pose = start.lerp(&end, t * t * (3.0 - 2.0 * t));
+		let _ = spatial.set_relative_transform(root, pose.transform());
+		on_step(pose);
+		if t >= 1.0 {
+			break;
+		}
+	}
+}
+
+/// Extract the position and heading of the user from the HMD transform, ignoring pitch and roll.
+pub fn body_frame(hmd: &Transform) -> (Vec3, Quat) {
+	let translation = hmd.translation.map(Vec3::from).unwrap_or_default();
+	let rotation = hmd.rotation.map(Quat::from).unwrap_or_default();
+	let forward = rotation * Vec3::NEG_Z;
+	(
+		translation,
+		Quat::from_rotation_y(f32::atan2(-forward.x, -forward.z)),
+	)
+}
+
+/// What the pose in a `PoseRequest` is relative to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PoseAnchor {
+	/// the client root
+	Stage,
+	/// the user's position and heading, but not their head tilt
+	Body,
+}
+
+/// Ask a panel to move to a pose. A new request is only applied if it differs from the last one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoseRequest {
+	id: u64,
+	pub anchor: PoseAnchor,
+	pub pose: PanelPose,
+	/// glide over from the current pose instead of jumping there
+	pub animated: bool,
+}
+impl PoseRequest {
+	pub fn new(anchor: PoseAnchor, pose: PanelPose) -> Self {
+		static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+		PoseRequest {
+			id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+			anchor,
+			pose,
+			animated: false,
+		}
+	}
+	pub fn animated(mut self) -> Self {
+		self.animated = true;
+		self
+	}
+}
@@ -1,20 +1,93 @@
 use glam::{FloatExt, Vec3};
 use rustc_hash::FxHashMap;
-use stardust_xr_asteroids::ClientState;
+use stardust_xr_asteroids::{
+	ClientState, Context, CreateInnerInfo, CustomElement, Transformable, ValidState,
+};
 use stardust_xr_fusion::{
 	drawable::{MaterialParameter, Model, ModelPart, ModelPartAspect},
 	fields::{Field, FieldRefAspect},
 	items::panel::{PanelItem, PanelItemAcceptor, PanelItemAcceptorAspect},
 	node::{NodeError, NodeType},
-	spatial::{SpatialAspect, Transform},
+	root::FrameInfo,
+	spatial::{Spatial, SpatialAspect, SpatialRef, Transform},
 	values::{color::rgba_linear, ResourceID},
 };
 use stardust_xr_molecules::input_action::SingleAction;
 use tokio::task::JoinSet;
 
-use crate::{grab_ball::GrabBallHead, State};
+use crate::{
+	grab_ball::{GrabBall, GrabBallHead, GrabBallSettings},
+	State,
+};
 
 const MAX_ACCEPT_DISTANCE: f32 = 0.05;
+
+/// Acceptors (e.g. panel shells) a panel can be handed over to, keyed by node id.
+pub type Acceptors = FxHashMap<u64, (PanelItemAcceptor, Field)>;
+
+/// A grab ball that hands the panel over to the closest acceptor when let go near one.
+#[derive(Debug, PartialEq)]
+pub struct PanelShellTransferBall {
+	pub transform: Transform,
+	pub panel_item: PanelItem,
+	pub acceptors: Acceptors,
+}
+impl<State: ValidState> CustomElement<State> for PanelShellTransferBall {
+	type Inner = GrabBall<PanelShellTransfer>;
+	type Resource = ();
+	type Error = NodeError;
+
+	fn create_inner(
+		&self,
+		_context: &Context,
+		info: CreateInnerInfo,
+		_resource: &mut Self::Resource,
+	) -> Result<Self::Inner, Self::Error> {
+		let connect_root = Spatial::create(info.parent_space, self.transform)?;
+		let head = PanelShellTransfer::create(&connect_root, self.panel_item.clone())?;
+		GrabBall::create(
+			connect_root,
+			[0.0, -0.02, 0.0],
+			head,
+			GrabBallSettings {
+				radius: 0.01252,
+				padding: 0.0,
+				connector_thickness: 0.0025,
+				connector_color: rgba_linear!(0.0, 1.0, 0.5, 1.0),
+			},
+		)
+	}
+
+	fn diff(&self, old: &Self, inner: &mut Self::Inner, _resource: &mut Self::Resource) {
+		self.apply_transform(old, inner.connect_root());
+	}
+
+	fn frame(
+		&self,
+		_context: &Context,
+		_info: &FrameInfo,
+		_state: &mut State,
+		inner: &mut Self::Inner,
+	) {
+		inner.update();
+		inner
+			.head
+			.update_distances(inner.grab_action(), &self.acceptors);
+	}
+
+	fn spatial_aspect(&self, inner: &Self::Inner) -> SpatialRef {
+		inner.connect_root().clone().as_spatial_ref()
+	}
+}
+impl Transformable for PanelShellTransferBall {
+	fn transform(&self) -> &Transform {
+		&self.transform
+	}
+	fn transform_mut(&mut self) -> &mut Transform {
+		&mut self.transform
+	}
+}
+
 pub struct PanelShellTransfer {
 	panel_item: PanelItem,
 	model: Model,
@@ -39,11 +112,7 @@ impl PanelShellTransfer {
 		})
 	}
 
-	pub fn update_distances(
-		&self,
-		grab_action: &SingleAction,
-		acceptors: &FxHashMap<u64, (PanelItemAcceptor, Field)>,
-	) {
+	pub fn update_distances(&self, grab_action: &SingleAction, acceptors: &Acceptors) {
 		if !grab_action.actor_acting() && !grab_action.actor_stopped() {
 			return;
 		}
@@ -91,7 +160,7 @@ impl PanelShellTransfer {
 					color.a as f32
 				)),
 			);
-			if released && dbg!(closest_distance) < MAX_ACCEPT_DISTANCE {
+			if released && closest_distance < MAX_ACCEPT_DISTANCE {
 				let _ = acceptor.capture_item(&panel_item);
 			}
 		});
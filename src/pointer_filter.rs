@@ -0,0 +1,174 @@
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+/// Cutoff for smoothing the pointer's speed, in Hz.
+const SPEED_CUTOFF: f32 = 1.0;
+/// How quickly the cursor catches back up with the pointer after precision mode, per second.
+const CATCH_UP_RATE: f32 = 6.0;
+/// How long a half press has to last for precision mode, in seconds,
+/// so pinching or pulling the trigger all the way through to click doesn't set it off on the way.
+const HALF_PRESS_DELAY: f32 = 0.15;
+
+/// How the cursor follows a pointer over a panel.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PointerFilterSettings {
+	/// smooth out jitter with a One Euro filter
+	pub smoothing: bool,
+	/// how much a still pointer is smoothed, in Hz, lower is steadier but lags more
+	pub min_cutoff: f32,
+	/// how quickly smoothing lets up as the pointer speeds up, per meter per second
+	pub beta: f32,
+	/// how much of the pointer's movement the cursor follows in precision mode
+	pub precision_gain: f32,
+	/// half pinching or pulling the trigger partway holds precision mode
+	pub half_press_precision: bool,
+	/// a button in the pointer's datamap, like "grab" for a controller's grip,
+	/// that holds precision mode for as long as it's held
+	pub precision_button: Option<String>,
+}
+impl Default for PointerFilterSettings {
+	fn default() -> Self {
+		PointerFilterSettings {
+			smoothing: true,
+			min_cutoff: 1.0,
+			beta: 10.0,
+			precision_gain: 0.25,
+			half_press_precision: true,
+			precision_button: None,
+		}
+	}
+}
+
+/// How much of the way to move towards a new value each step of `delta` seconds for a low pass at `cutoff` Hz.
+fn smoothing_factor(cutoff: f32, delta: f32) -> f32 {
+	let tau = 1.0 / (TAU * cutoff.max(f32::EPSILON));
+	1.0 / (1.0 + tau / delta)
+}
+
+/// Smooths out a pointer on a panel, and slows it down in precision mode.
+/// Positions are in meters on the panel.
+#[derive(Debug, Default)]
+pub struct PointerFilter {
+	smoothed: Option<Vec2>,
+	/// in meters per second
+	speed: Vec2,
+	/// how far the cursor has been held back from the pointer by precision mode
+	offset: Vec2,
+	/// how long the pointer has been half pressed for, in seconds
+	half_pressed_for: Option<f32>,
+}
+impl PointerFilter {
+	/// Start over, e.g. for a different pointer.
+	pub fn reset(&mut self) {
+		*self = PointerFilter::default();
+	}
+
+	/// Whether to be in precision mode, `delta` seconds since the last update:
+	/// while `button_held`, or once `half_pressed` has lasted long enough to not just be on the way to a click.
+	pub fn precise(
+		&mut self,
+		settings: &PointerFilterSettings,
+		half_pressed: bool,
+		button_held: bool,
+		delta: f32,
+	) -> bool {
+		self.half_pressed_for = (half_pressed && settings.half_press_precision)
+			.then(|| self.half_pressed_for.unwrap_or(0.0) + delta);
+		button_held
+			|| self
+				.half_pressed_for
+				.is_some_and(|time| time >= HALF_PRESS_DELAY)
+	}
+
+	/// Where the cursor should be for the pointer at `raw`, `delta` seconds since the last update.
+	/// `pressed` keeps any precision offset so clicks and drags land where they were aimed.
+	pub fn update(
+		&mut self,
+		settings: &PointerFilterSettings,
+		raw: Vec2,
+		delta: f32,
+		precise: bool,
+		pressed: bool,
+	) -> Vec2 {
+		let Some(last) = self.smoothed else {
+			self.smoothed = Some(raw);
+			return raw + self.offset;
+		};
+		if delta <= 0.0 {
+			return last + self.offset;
+		}
+		let position = if settings.smoothing {
+			let speed = (raw - last) / delta;
+			self.speed = self
+				.speed
+				.lerp(speed, smoothing_factor(SPEED_CUTOFF, delta));
+			let cutoff = settings.min_cutoff + settings.beta * self.speed.length();
+			last.lerp(raw, smoothing_factor(cutoff, delta))
+		} else {
+			raw
+		};
+		self.smoothed = Some(position);
+
+		if precise {
+			let gain = settings.precision_gain.clamp(0.0, 1.0);
+			self.offset -= (position - last) * (1.0 - gain);
+		} else if !pressed {
+			self.offset *= (-CATCH_UP_RATE * delta).exp();
+		}
+		position + self.offset
+	}
+}
+
+#[test]
+fn smooths_jitter_and_slows_down() {
+	use glam::vec2;
+	let settings = PointerFilterSettings::default();
+	let mut filter = PointerFilter::default();
+	filter.update(&settings, Vec2::ZERO, 0.011, false, false);
+	// jitter around a still point barely moves the cursor
+	for i in 0..100 {
+		let jitter = if i % 2 == 0 { 0.002 } else { -0.002 };
+		let cursor = filter.update(&settings, vec2(jitter, 0.0), 0.011, false, false);
+		assert!(cursor.x.abs() < 0.001);
+	}
+
+	let mut filter = PointerFilter::default();
+	let no_smoothing = PointerFilterSettings {
+		smoothing: false,
+		..settings
+	};
+	filter.update(&no_smoothing, Vec2::ZERO, 0.011, false, false);
+	let cursor = filter.update(&no_smoothing, vec2(0.1, 0.0), 0.011, true, false);
+	assert!((cursor.x - 0.1 * settings.precision_gain).abs() < 0.0001);
+	// stays put while clicking, then catches back up with the pointer
+	let cursor = filter.update(&no_smoothing, vec2(0.1, 0.0), 0.011, false, true);
+	assert!((cursor.x - 0.1 * settings.precision_gain).abs() < 0.0001);
+	let cursor = (0..200)
+		.map(|_| filter.update(&no_smoothing, vec2(0.1, 0.0), 0.011, false, false))
+		.last()
+		.unwrap();
+	assert!((cursor.x - 0.1).abs() < 0.0001);
+}
+
+#[test]
+fn clicking_passes_through_half_press() {
+	let settings = PointerFilterSettings::default();
+	let mut filter = PointerFilter::default();
+	// a pinch closing in a few frames doesn't slow the cursor on the way
+	assert!(!filter.precise(&settings, false, false, 0.011));
+	for _ in 0..5 {
+		assert!(!filter.precise(&settings, true, false, 0.011));
+	}
+	assert!(!filter.precise(&settings, false, false, 0.011));
+	// holding a half pinch does
+	let precise = (0..20)
+		.map(|_| filter.precise(&settings, true, false, 0.011))
+		.collect::<Vec<_>>();
+	assert!(!precise[0]);
+	assert!(precise[19]);
+	// the button is right away
+	let mut filter = PointerFilter::default();
+	assert!(filter.precise(&settings, false, true, 0.011));
+}
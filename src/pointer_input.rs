@@ -6,7 +6,7 @@ use stardust_xr_asteroids::{
 };
 use stardust_xr_fusion::{
 	drawable::{Line, LinePoint, Lines, LinesAspect},
-	fields::{Field, FieldAspect, Shape},
+	fields::{Field, FieldAspect},
 	input::{Finger, Hand, InputData, InputDataType, InputHandler},
 	node::{NodeError, NodeType},
 	root::FrameInfo,
@@ -21,10 +21,112 @@ use stardust_xr_molecules::{
 };
 use std::{sync::Arc, time::Duration};
 
+use crate::{
+	curved_panel::{self, field_shape},
+	hand_gestures::{self, Click, HandGestureSettings, HandGestures, HandInput},
+	pointer_filter::{PointerFilter, PointerFilterSettings},
+	preferences::DEFAULT_DENSITY,
+};
+
+/// How far the trigger has to be pulled for precision mode, short of clicking at 0.5.
+const PRECISION_TRIGGER: f32 = 0.1;
+
+/// How fast a fully tilted thumbstick scrolls, in px per second.
+const THUMBSTICK_SCROLL_SPEED: f32 = 1000.0;
+/// How far one mouse wheel click scrolls, in px, the same as most desktops.
+const WHEEL_STEP_DISTANCE: f32 = 15.0;
+/// How far smooth scrolling pushes or pulls something held at the end of a pointer's ray, in meters per px.
+const PUSH_PULL_DISTANCE: f32 = 0.01;
+/// How far a mouse wheel click pushes or pulls something held at the end of a pointer's ray, in meters.
+const PUSH_PULL_STEP: f32 = 0.05;
+
+/// Scrolling from one frame, +y up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScrollEvent {
+	/// mouse wheel clicks, fractions of one from high resolution wheels,
+	/// plus any smooth scrolling in px that came along in the same frame
+	Wheel { steps: Vec2, distance: Vec2 },
+	/// smooth scrolling from a touchpad, thumbstick or hand gesture, in px
+	Continuous(Vec2),
+	/// smooth scrolling just ended, so apps can start kinetic scrolling
+	Stop,
+}
+impl ScrollEvent {
+	/// Whether any smooth scrolling is in it, which has to end with a `Stop`.
+	pub fn smooth(&self) -> bool {
+		match self {
+			ScrollEvent::Wheel { distance, .. } => *distance != Vec2::ZERO,
+			ScrollEvent::Continuous(_) => true,
+			ScrollEvent::Stop => false,
+		}
+	}
+	/// How far to scroll the app in px and in wheel clicks, `multiplier` times as far
+	/// and flipped to +y down like it expects, or nothing for a `Stop`.
+	pub fn for_app(&self, multiplier: f32) -> Option<(Vec2, Vec2)> {
+		let flip = vec2(1.0, -1.0) * multiplier;
+		match *self {
+			ScrollEvent::Wheel { steps, distance } => Some((
+				((steps * WHEEL_STEP_DISTANCE) + distance) * flip,
+				steps * flip,
+			)),
+			ScrollEvent::Continuous(distance) => Some((distance * flip, Vec2::ZERO)),
+			ScrollEvent::Stop => None,
+		}
+	}
+}
+
+/// What pointers put in their datamap for scrolling.
 #[derive(Debug, Default, Clone, Deserialize)]
-pub struct MouseEvent {
-	pub scroll_continuous: Option<Vector2<f32>>,
-	pub scroll_discrete: Option<Vector2<f32>>,
+pub struct ScrollData {
+	/// in px
+	scroll_continuous: Option<Vector2<f32>>,
+	/// in wheel clicks
+	scroll_discrete: Option<Vector2<f32>>,
+	/// how far a thumbstick is tilted
+	scroll: Option<Vector2<f32>>,
+}
+impl ScrollData {
+	/// What `input` scrolled this frame, nothing if it doesn't scroll.
+	pub fn of(input: &InputData) -> Self {
+		input.datamap.deserialize().unwrap_or_default()
+	}
+	/// How far to push something held at the end of the pointer's ray away along it, in meters,
+	/// negative to pull it closer.
+	pub fn push_pull(&self) -> f32 {
+		let y = |v: Option<Vector2<f32>>| v.map(|v| v.y).unwrap_or_default();
+		(y(self.scroll_continuous) * PUSH_PULL_DISTANCE)
+			+ (y(self.scroll_discrete) * PUSH_PULL_STEP)
+	}
+	/// Everything to send for this frame, ending smooth scrolling with a `Stop` if it did,
+	/// with whether it's `scrolling` smoothly kept up to date.
+	fn events(&self, delta: f32, extra: Vec2, scrolling: &mut bool) -> Vec<ScrollEvent> {
+		let was_scrolling = *scrolling;
+		let scroll = self.event(delta, extra, was_scrolling);
+		*scrolling = scroll.is_some_and(|scroll| scroll.smooth());
+		let mut events = Vec::new();
+		// a wheel click in the frame smooth scrolling ended in still needs the stop
+		if was_scrolling && !*scrolling && scroll != Some(ScrollEvent::Stop) {
+			events.push(ScrollEvent::Stop);
+		}
+		events.extend(scroll);
+		events
+	}
+	/// What to send for this frame, with `delta` seconds since the last one
+	/// and `extra` px of smooth scrolling from elsewhere.
+	fn event(&self, delta: f32, extra: Vec2, was_scrolling: bool) -> Option<ScrollEvent> {
+		let vec = |v: Option<Vector2<f32>>| v.map(|v| vec2(v.x, v.y)).unwrap_or_default();
+		let steps = vec(self.scroll_discrete);
+		let distance = vec(self.scroll_continuous)
+			+ (vec(self.scroll) * THUMBSTICK_SCROLL_SPEED * delta)
+			+ extra;
+		if steps != Vec2::ZERO {
+			Some(ScrollEvent::Wheel { steps, distance })
+		} else if distance != Vec2::ZERO {
+			Some(ScrollEvent::Continuous(distance))
+		} else {
+			was_scrolling.then_some(ScrollEvent::Stop)
+		}
+	}
 }
 
 #[derive_where::derive_where(Debug, PartialEq)]
@@ -37,13 +139,22 @@ pub struct PointerPlane<State: ValidState> {
 	pub thickness: f32,
 	pub click_freeze_time: Duration,
 	pub debug_line_settings: Option<DebugSettings>,
+	/// bend the plane around a vertical cylinder of this radius, see `curved_panel`
+	#[setters(strip_option = false)]
+	pub curve_radius: Option<f32>,
+	/// px per meter, for how far hand gestures scroll
+	pub density: f32,
+	/// smoothing and precision mode for the cursor
+	pub filter: PointerFilterSettings,
+	/// how hands click and scroll
+	pub hand_gestures: HandGestureSettings,
 
 	#[setters(skip)]
 	pub on_mouse_button: FnWrapper<dyn Fn(&mut State, u32, bool) + Send + Sync>,
 	#[setters(skip)]
 	pub on_pointer_motion: FnWrapper<dyn Fn(&mut State, Vector3<f32>) + Send + Sync>,
 	#[setters(skip)]
-	pub on_scroll: FnWrapper<dyn Fn(&mut State, MouseEvent) + Send + Sync>,
+	pub on_scroll: FnWrapper<dyn Fn(&mut State, ScrollEvent) + Send + Sync>,
 }
 
 impl<State: ValidState> Default for PointerPlane<State> {
@@ -54,6 +165,10 @@ impl<State: ValidState> Default for PointerPlane<State> {
 			thickness: 0.0,
 			click_freeze_time: Duration::from_millis(300),
 			debug_line_settings: None,
+			curve_radius: None,
+			density: DEFAULT_DENSITY,
+			filter: PointerFilterSettings::default(),
+			hand_gestures: HandGestureSettings::default(),
 
 			on_mouse_button: FnWrapper(Box::new(|_, _, _| {})),
 			on_pointer_motion: FnWrapper(Box::new(|_, _| {})),
@@ -79,7 +194,10 @@ impl<State: ValidState> PointerPlane<State> {
 		self
 	}
 
-	pub fn on_scroll(mut self, f: impl Fn(&mut State, MouseEvent) + Send + Sync + 'static) -> Self {
+	pub fn on_scroll(
+		mut self,
+		f: impl Fn(&mut State, ScrollEvent) + Send + Sync + 'static,
+	) -> Self {
 		self.on_scroll = FnWrapper(Box::new(f));
 		self
 	}
@@ -99,7 +217,7 @@ impl<State: ValidState> CustomElement<State> for PointerPlane<State> {
 		let field = Field::create(
 			info.parent_space,
 			self.transform,
-			Shape::Box([self.physical_size.x, self.physical_size.y, self.thickness].into()),
+			field_shape(self.physical_size.into(), self.thickness, self.curve_radius),
 		)?;
 
 		let input = InputHandler::create(&field, Transform::none(), &field)?.queue()?;
@@ -115,8 +233,13 @@ impl<State: ValidState> CustomElement<State> for PointerPlane<State> {
 			middle_click: SingleAction::default(),
 			right_click: SingleAction::default(),
 			start_click_time: 0.0,
+			scrolling: false,
+			hand_gestures: HandGestures::default(),
+			pointer_filter: PointerFilter::default(),
+			filtered_input: None,
 			physical_size: self.physical_size.into(),
 			thickness: self.thickness,
+			curve_radius: self.curve_radius,
 			lines,
 			debug_line_settings: self.debug_line_settings,
 		})
@@ -127,7 +250,8 @@ impl<State: ValidState> CustomElement<State> for PointerPlane<State> {
 		if self.debug_line_settings != old.debug_line_settings {
 			inner.set_debug(self.debug_line_settings);
 		}
-		if self.physical_size != old.physical_size {
+		if self.physical_size != old.physical_size || self.curve_radius != old.curve_radius {
+			inner.curve_radius = self.curve_radius;
 			inner.resize(self.physical_size.into());
 		}
 	}
@@ -165,8 +289,15 @@ pub struct PointerSurfaceInputInner {
 	middle_click: SingleAction,
 	right_click: SingleAction,
 	start_click_time: f32,
+	/// smooth scrolling last frame, to know when to send a stop
+	scrolling: bool,
+	hand_gestures: HandGestures,
+	pointer_filter: PointerFilter,
+	/// the input `pointer_filter` is following
+	filtered_input: Option<u64>,
 	physical_size: Vec2,
 	thickness: f32,
+	curve_radius: Option<f32>,
 	lines: Lines,
 	debug_line_settings: Option<DebugSettings>,
 }
@@ -187,8 +318,10 @@ impl PointerSurfaceInputInner {
 
 	pub fn resize(&mut self, physical_size: Vec2) {
 		self.physical_size = physical_size;
-		let _ = self.field.set_shape(Shape::Box(
-			[physical_size.x, physical_size.y, self.thickness].into(),
+		let _ = self.field.set_shape(field_shape(
+			physical_size,
+			self.thickness,
+			self.curve_radius,
 		));
 	}
 
@@ -202,13 +335,22 @@ impl PointerSurfaceInputInner {
 			&& point.z.is_sign_positive() == front
 	}
 
-	fn hover_point(input: &InputData, stable: bool) -> Vec3 {
-		match &input.input {
+	/// Where the input is over the plane, in flat coordinates if it's curved.
+	fn hover_point(input: &InputData, stable: bool, curve_radius: Option<f32>) -> Vec3 {
+		let point = match &input.input {
 			InputDataType::Pointer(p) => {
-				let normal = vec3(0.0, 0.0, 1.0);
-				let denom = normal.dot(p.direction().into());
-				let t = -Vec3::from(p.origin).dot(normal) / denom;
-				Vec3::from(p.origin) + Vec3::from(p.direction()) * t
+				let origin = Vec3::from(p.origin);
+				let direction = Vec3::from(p.direction());
+				if let Some(hit) =
+					curve_radius.and_then(|radius| curved_panel::ray_hit(origin, direction, radius))
+				{
+					hit
+				} else {
+					let normal = vec3(0.0, 0.0, 1.0);
+					let denom = normal.dot(direction);
+					let t = -origin.dot(normal) / denom;
+					origin + direction * t
+				}
 			}
 			InputDataType::Hand(h) => {
 				if stable {
@@ -218,6 +360,43 @@ impl PointerSurfaceInputInner {
 				}
 			}
 			InputDataType::Tip(t) => t.origin.into(),
+		};
+		match curve_radius {
+			Some(radius) => curved_panel::flatten(point, radius),
+			None => point,
+		}
+	}
+
+	/// Flat coordinates back to the field's space.
+	fn bend(&self, point: Vec3) -> Vec3 {
+		match self.curve_radius {
+			Some(radius) => curved_panel::bend(point, radius),
+			None => point,
+		}
+	}
+
+	/// What the hovering hand is doing, if it's a hand.
+	fn hand_input(&self, input: &InputData) -> HandInput {
+		let InputDataType::Hand(hand) = &input.input else {
+			return HandInput::default();
+		};
+		let on_plane = |point: Vec3| match self.curve_radius {
+			Some(radius) => curved_panel::flatten(point, radius).truncate(),
+			None => point.truncate(),
+		};
+		HandInput {
+			pinch: hand_gestures::pinch(hand).map(on_plane),
+			two_fingers: hand_gestures::two_fingers(hand).map(on_plane),
+		}
+	}
+
+	/// Partway into a click, for precision mode: a half pinch, or the trigger pulled partway.
+	fn half_pressed(input: &InputData) -> bool {
+		match &input.input {
+			InputDataType::Hand(hand) => hand_gestures::half_pinch(hand),
+			_ => input
+				.datamap
+				.with_data(|d| (PRECISION_TRIGGER..0.5).contains(&d.idx("select").as_f32())),
 		}
 	}
 
@@ -238,7 +417,8 @@ impl PointerSurfaceInputInner {
 		elapsed_time: f32,
 		start_click_time: &mut f32,
 		action: &mut SingleAction,
-		finger: fn(&Hand) -> &Finger,
+		// hands that pinch with this finger press the button, if any
+		finger: Option<fn(&Hand) -> &Finger>,
 		datamap_key: &str,
 		button: u32,
 		closest_hover: Option<Arc<InputData>>,
@@ -248,11 +428,11 @@ impl PointerSurfaceInputInner {
 			input,
 			|input| Some(input.id) == closest_hover.clone().map(|c| c.id),
 			|input| match &input.input {
-				InputDataType::Hand(h) => {
+				InputDataType::Hand(h) => finger.is_some_and(|finger| {
 					let thumb_tip = Vec3::from(h.thumb.tip.position);
 					let finger_tip = Vec3::from((finger)(h).tip.position);
 					thumb_tip.distance(finger_tip) < 0.02
-				}
+				}),
 				_ => input
 					.datamap
 					.with_data(|d| d.idx(datamap_key).as_f32() > 0.5),
@@ -273,10 +453,21 @@ impl PointerSurfaceInputInner {
 		decl: &PointerPlane<State>,
 		frame_info: &FrameInfo,
 	) {
+		let curve_radius = self.curve_radius;
 		self.hover.update(&self.input, &|input| match &input.input {
+			// the field of a curved plane is a box around it, so make sure the ray hits the plane itself
+			InputDataType::Pointer(_) if curve_radius.is_some() => {
+				let hover_point = Self::hover_point(input, true, curve_radius);
+				input.distance <= 0.0
+					&& Self::hovering(
+						self.physical_size.into(),
+						[hover_point.x, hover_point.y, 0.0].into(),
+						true,
+					)
+			}
 			InputDataType::Pointer(_) => input.distance <= 0.0,
 			_ => {
-				let hover_point = Self::hover_point(input, true);
+				let hover_point = Self::hover_point(input, true, curve_radius);
 				(0.05..0.2).contains(&hover_point.z.abs())
 					&& Self::hovering(self.physical_size.into(), hover_point.into(), true)
 			}
@@ -297,7 +488,8 @@ impl PointerSurfaceInputInner {
 			frame_info.elapsed,
 			&mut self.start_click_time,
 			&mut self.left_click,
-			|hand| &hand.index,
+			// hands left click through `hand_gestures` so pinching can scroll too
+			None,
 			"select",
 			input_event_codes::BTN_LEFT!(),
 			self.pointer_hover.clone(),
@@ -310,7 +502,7 @@ impl PointerSurfaceInputInner {
 			frame_info.elapsed,
 			&mut self.start_click_time,
 			&mut self.middle_click,
-			|hand| &hand.middle,
+			Some(|hand| &hand.middle),
 			"middle",
 			input_event_codes::BTN_MIDDLE!(),
 			self.pointer_hover.clone(),
@@ -323,47 +515,82 @@ impl PointerSurfaceInputInner {
 			frame_info.elapsed,
 			&mut self.start_click_time,
 			&mut self.right_click,
-			|hand| &hand.ring,
+			Some(|hand| &hand.ring),
 			"context",
 			input_event_codes::BTN_RIGHT!(),
 			self.pointer_hover.clone(),
 		);
 
-		let Some(closest_hover) = self.pointer_hover.clone() else {
-			return;
-		};
-
-		let position = self.to_local_coords(Self::hover_point(&closest_hover, true));
-		if frame_info.elapsed - self.start_click_time > decl.click_freeze_time.as_secs_f32() {
-			(decl.on_pointer_motion.0)(state, position);
-		}
-
-		let mouse_event = closest_hover
-			.datamap
-			.deserialize::<MouseEvent>()
+		let hand_input = self
+			.pointer_hover
+			.as_ref()
+			.map(|input| self.hand_input(input))
 			.unwrap_or_default();
-		(decl.on_scroll.0)(state, mouse_event);
+		let gesture = self
+			.hand_gestures
+			.update(&decl.hand_gestures, hand_input, frame_info.delta);
+		if let Some(click) = gesture.left_click {
+			let left = input_event_codes::BTN_LEFT!();
+			if click != Click::Release {
+				self.start_click_time = frame_info.elapsed;
+				(decl.on_mouse_button.0)(state, left, true);
+			}
+			if click != Click::Press {
+				(decl.on_mouse_button.0)(state, left, false);
+			}
+		}
 
-		#[derive(Deserialize, Default)]
-		struct ScrollInput {
-			scroll: Option<Vector2<f32>>,
+		if let Some(closest_hover) = &self.pointer_hover {
+			if self.filtered_input != Some(closest_hover.id) {
+				self.pointer_filter.reset();
+				self.filtered_input = Some(closest_hover.id);
+			}
+			let pressed = self.left_click.actor_acting()
+				|| self.middle_click.actor_acting()
+				|| self.right_click.actor_acting()
+				|| hand_input.pinch.is_some();
+			let point = Self::hover_point(closest_hover, true, self.curve_radius);
+			let button_held = decl
+				.filter
+				.precision_button
+				.as_deref()
+				.is_some_and(|button| {
+					closest_hover
+						.datamap
+						.with_data(|d| d.idx(button).as_f32() > 0.5)
+				});
+			let precise = self.pointer_filter.precise(
+				&decl.filter,
+				Self::half_pressed(closest_hover),
+				button_held,
+				frame_info.delta,
+			);
+			let filtered = self.pointer_filter.update(
+				&decl.filter,
+				point.truncate(),
+				frame_info.delta,
+				precise,
+				pressed,
+			);
+			let position = self.to_local_coords(filtered.extend(point.z));
+			if frame_info.elapsed - self.start_click_time > decl.click_freeze_time.as_secs_f32()
+				&& !gesture.holding_cursor
+			{
+				(decl.on_pointer_motion.0)(state, position);
+			}
 		}
 
-		let scroll = closest_hover
-			.datamap
-			.deserialize::<ScrollInput>()
+		// dragging content up scrolls down, like touchscreens
+		let hand_scroll = -gesture.scroll * decl.density;
+		let scroll = self
+			.pointer_hover
+			.as_deref()
+			.map(ScrollData::of)
 			.unwrap_or_default()
-			.scroll;
-		(decl.on_scroll.0)(
-			state,
-			MouseEvent {
-				// TODO: fix the server, we're not sending some events some apps need to register
-				// continuous scroll, we should send that instead of discrete
-				scroll_continuous: None,
-				scroll_discrete: scroll
-					.map(|scroll| (vec2(scroll.x, scroll.y) * frame_info.delta).into()),
-			},
-		);
+			.events(frame_info.delta, hand_scroll, &mut self.scrolling);
+		for scroll in scroll {
+			(decl.on_scroll.0)(state, scroll);
+		}
 	}
 
 	fn update_signifiers(&mut self) {
@@ -409,8 +636,8 @@ impl PointerSurfaceInputInner {
 			None
 		} else {
 			Some(self.line_from_point(
-				PointerSurfaceInputInner::hover_point(input, true),
-				PointerSurfaceInputInner::hover_point(input, false),
+				Self::hover_point(input, true, self.curve_radius),
+				Self::hover_point(input, false, self.curve_radius),
 				interacting,
 			))
 		}
@@ -421,16 +648,17 @@ impl PointerSurfaceInputInner {
 		Line {
 			points: vec![
 				LinePoint {
-					point: [
-						stable_point
-							.x
-							.clamp(self.physical_size.x * -0.5, self.physical_size.x * 0.5),
-						stable_point
-							.y
-							.clamp(self.physical_size.y * -0.5, self.physical_size.y * 0.5),
-						0.0,
-					]
-					.into(),
+					point: self
+						.bend(vec3(
+							stable_point
+								.x
+								.clamp(self.physical_size.x * -0.5, self.physical_size.x * 0.5),
+							stable_point
+								.y
+								.clamp(self.physical_size.y * -0.5, self.physical_size.y * 0.5),
+							0.0,
+						))
+						.into(),
 					thickness: settings.line_start_thickness,
 					color: if interacting {
 						settings.line_start_color_interact
@@ -439,7 +667,7 @@ impl PointerSurfaceInputInner {
 					},
 				},
 				LinePoint {
-					point: unstable_point.into(),
+					point: self.bend(unstable_point).into(),
 					thickness: settings.line_end_thickness,
 					color: if interacting {
 						settings.line_end_color_interact
@@ -458,3 +686,87 @@ impl VisualDebug for PointerSurfaceInputInner {
 		self.debug_line_settings = settings;
 	}
 }
+
+#[test]
+fn scroll_events() {
+	let data = |continuous: Option<Vec2>, discrete: Option<Vec2>| ScrollData {
+		scroll_continuous: continuous.map(Into::into),
+		scroll_discrete: discrete.map(Into::into),
+		scroll: None,
+	};
+	let mut scrolling = false;
+	// a wheel click, then a fraction of one from a high resolution wheel
+	let wheel = data(None, Some(vec2(0.0, 1.0))).events(0.011, Vec2::ZERO, &mut scrolling);
+	assert_eq!(
+		wheel,
+		vec![ScrollEvent::Wheel {
+			steps: vec2(0.0, 1.0),
+			distance: Vec2::ZERO
+		}]
+	);
+	assert!(!scrolling);
+	let hi_res =
+		data(Some(vec2(0.0, 4.0)), Some(vec2(0.0, 0.25))).events(0.011, Vec2::ZERO, &mut scrolling);
+	assert_eq!(
+		hi_res,
+		vec![ScrollEvent::Wheel {
+			steps: vec2(0.0, 0.25),
+			distance: vec2(0.0, 4.0)
+		}]
+	);
+	// the px that came with it were smooth, so they end with a stop
+	assert!(scrolling);
+	let stop = data(None, None).events(0.011, Vec2::ZERO, &mut scrolling);
+	assert_eq!(stop, vec![ScrollEvent::Stop]);
+	assert!(data(None, None)
+		.events(0.011, Vec2::ZERO, &mut scrolling)
+		.is_empty());
+
+	// a touchpad, then a wheel click right as it ends
+	let touchpad = data(Some(vec2(2.0, 0.0)), None).events(0.011, Vec2::ZERO, &mut scrolling);
+	assert_eq!(touchpad, vec![ScrollEvent::Continuous(vec2(2.0, 0.0))]);
+	let wheel = data(None, Some(vec2(0.0, -1.0))).events(0.011, Vec2::ZERO, &mut scrolling);
+	assert_eq!(
+		wheel,
+		vec![
+			ScrollEvent::Stop,
+			ScrollEvent::Wheel {
+				steps: vec2(0.0, -1.0),
+				distance: Vec2::ZERO
+			}
+		]
+	);
+	assert!(!scrolling);
+
+	// hand gestures scroll smoothly too
+	let hand = data(None, None).events(0.011, vec2(0.0, 30.0), &mut scrolling);
+	assert_eq!(hand, vec![ScrollEvent::Continuous(vec2(0.0, 30.0))]);
+}
+
+#[test]
+fn scroll_for_app_goes_down() {
+	let wheel = ScrollEvent::Wheel {
+		steps: vec2(0.0, 1.0),
+		distance: vec2(0.0, 5.0),
+	};
+	assert_eq!(
+		wheel.for_app(1.0),
+		Some((vec2(0.0, -(WHEEL_STEP_DISTANCE + 5.0)), vec2(0.0, -1.0)))
+	);
+	assert_eq!(
+		ScrollEvent::Continuous(vec2(3.0, 2.0)).for_app(2.0),
+		Some((vec2(6.0, -4.0), Vec2::ZERO))
+	);
+	assert_eq!(ScrollEvent::Stop.for_app(1.0), None);
+}
+
+#[test]
+fn scrolling_pushes_and_pulls() {
+	let scroll = ScrollData {
+		scroll_continuous: Some(vec2(0.0, 10.0).into()),
+		scroll_discrete: Some(vec2(0.0, -1.0).into()),
+		scroll: None,
+	};
+	assert!((scroll.push_pull() - (10.0 * PUSH_PULL_DISTANCE - PUSH_PULL_STEP)).abs() < 0.0001);
+	assert_eq!(ScrollData::default().push_pull(), 0.0);
+}
@@ -0,0 +1,155 @@
+use crate::{anchoring::Anchor, panel_pose::PanelPose};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_DENSITY: f32 = 3000.0;
+
+/// Everything Flatland remembers about an app between sessions, keyed by app_id.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToplevelPreferences {
+	/// pixels per meter
+	pub density: f32,
+	/// in px
+	pub size: Option<[u32; 2]>,
+	/// last pose the panel was left at, relative to the user's position and heading
+	/// (like `PoseAnchor::Body`) if it isn't world-locked
+	pub pose: Option<PanelPose>,
+	pub scroll_multiplier: f32,
+	pub aspect_lock: bool,
+	/// radius of the cylinder the panel is bent around, flat if none
+	pub curve_radius: Option<f32>,
+	/// name of the workspace the app was last on
+	pub workspace: Option<String>,
+	/// how the app's windows are placed when they open, instead of the usual `placement`
+	pub placement: Option<PlacementStrategy>,
+	/// what the app's windows stay put relative to
+	pub anchor: Anchor,
+	/// how touching the app's windows reaches it
+	pub touch_mode: TouchMode,
+	/// what pinching two fingers does in `TouchMode::Pointer`
+	pub pinch_zoom: PinchZoom,
+}
+impl Default for ToplevelPreferences {
+	fn default() -> Self {
+		ToplevelPreferences {
+			density: DEFAULT_DENSITY,
+			size: None,
+			pose: None,
+			scroll_multiplier: 1.0,
+			aspect_lock: false,
+			curve_radius: None,
+			workspace: None,
+			placement: None,
+			anchor: Anchor::World,
+			touch_mode: TouchMode::Touch,
+			pinch_zoom: PinchZoom::CtrlScroll,
+		}
+	}
+}
+
+/// How touching a panel reaches the app.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TouchMode {
+	/// as touches, for apps that support touch
+	#[default]
+	Touch,
+	/// as the mouse, with taps and drags for left click, a long press for right click,
+	/// and two fingers to scroll or pinch to zoom, for apps that don't (like most X11 apps)
+	Pointer,
+}
+impl TouchMode {
+	/// The other mode, for toggling from the toolbar.
+	pub fn next(self) -> Self {
+		match self {
+			TouchMode::Touch => TouchMode::Pointer,
+			TouchMode::Pointer => TouchMode::Touch,
+		}
+	}
+	pub fn label(self) -> &'static str {
+		match self {
+			TouchMode::Touch => "touch",
+			TouchMode::Pointer => "mouse",
+		}
+	}
+}
+
+/// What pinching two fingers on a panel does when touches act as the mouse.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PinchZoom {
+	/// hold ctrl and turn the mouse wheel, which most apps zoom their content with
+	#[default]
+	CtrlScroll,
+	/// scale the whole window up or down, like the scale handles do, for apps that don't zoom
+	Scale,
+}
+impl PinchZoom {
+	/// The other mode, for toggling from the toolbar.
+	pub fn next(self) -> Self {
+		match self {
+			PinchZoom::CtrlScroll => PinchZoom::Scale,
+			PinchZoom::Scale => PinchZoom::CtrlScroll,
+		}
+	}
+	pub fn label(self) -> &'static str {
+		match self {
+			PinchZoom::CtrlScroll => "zoom",
+			PinchZoom::Scale => "grow",
+		}
+	}
+}
+
+/// The virtual screen a toplevel is put on when it goes fullscreen.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FullscreenSettings {
+	/// in px
+	pub resolution: [u32; 2],
+	/// in meters
+	pub width: f32,
+	/// from the user, in meters
+	pub distance: f32,
+}
+impl Default for FullscreenSettings {
+	fn default() -> Self {
+		FullscreenSettings {
+			resolution: [1920, 1080],
+			width: 2.5,
+			distance: 2.5,
+		}
+	}
+}
+
+/// How a new toplevel is placed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlacementStrategy {
+	/// where the app was last left, otherwise `InFront`
+	Remembered,
+	/// in front of the user's head, facing them
+	InFront,
+	/// beside the panel that was used last
+	NextToFocused,
+	/// where it'd go if tiling was on
+	TilingSlot,
+	/// at the cursor on the panel that was used last
+	Pointer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlacementSettings {
+	/// for apps that don't have their own `placement`
+	pub strategy: PlacementStrategy,
+	/// how far in front of the user `InFront` puts panels, in meters
+	pub distance: f32,
+	/// glide new panels over from just in front of the user
+	pub fly_in: bool,
+}
+impl Default for PlacementSettings {
+	fn default() -> Self {
+		PlacementSettings {
+			strategy: PlacementStrategy::Remembered,
+			distance: 0.25,
+			fly_in: true,
+		}
+	}
+}
@@ -1,13 +1,21 @@
-use crate::{grab_ball::GrabBallSettings, State};
+use crate::{
+	anchoring::{follow, Anchor},
+	curved_panel,
+	grab_ball::GrabBallSettings,
+	panel_pose::{body_frame, glide, PanelPose, PoseAnchor, PoseRequest},
+	pointer_input::ScrollData,
+	snapping::{snap, upright, Snap, SnapSettings, SnapTarget, UprightSettings},
+	State,
+};
 use derive_setters::Setters;
-use glam::{vec2, vec3, Mat4, Quat, Vec3, Vec3Swizzles};
+use glam::{vec2, Mat4, Quat, Vec2, Vec3, Vec3Swizzles};
 use stardust_xr_asteroids::{
 	ClientState, Context, CreateInnerInfo, CustomElement, FnWrapper, ValidState,
 };
 use stardust_xr_fusion::{
-	drawable::{MaterialParameter, Model, ModelPart, ModelPartAspect},
+	drawable::{Lines, LinesAspect, MaterialParameter, Model, ModelPart, ModelPartAspect},
 	fields::{Field, FieldAspect, Shape},
-	input::{InputDataType, InputHandler},
+	input::{InputData, InputDataType, InputHandler},
 	node::{NodeError, NodeResult, NodeType},
 	objects::hmd,
 	objects::zbus::Connection,
@@ -19,6 +27,7 @@ use stardust_xr_fusion::{
 use stardust_xr_molecules::{
 	dbus::AbortOnDrop,
 	input_action::{InputQueue, InputQueueable, SingleAction},
+	lines::{line_from_points, rounded_rectangle, LineExt},
 	reparentable::Reparentable,
 	UIElement,
 };
@@ -26,9 +35,233 @@ use std::{
 	f32::consts::FRAC_PI_2,
 	path::{Path, PathBuf},
 };
-use tokio::sync::watch;
+use tokio::{sync::watch, task::JoinHandle};
 
 const RESIZE_HANDLE_FLOATING: f32 = 0.025;
+/// which corner each corner handle sits on, +x is right and +y is up
+const CORNER_SIGNS: [[f32; 2]; 4] = [[-1.0, -1.0], [1.0, -1.0], [-1.0, 1.0], [1.0, 1.0]];
+/// same for the handles on the middle of each edge
+const EDGE_SIGNS: [[f32; 2]; 4] = [[0.0, 1.0], [0.0, -1.0], [-1.0, 0.0], [1.0, 0.0]];
+
+/// Where a handle sits relative to the center of a panel of `size`.
+fn handle_offset(sign: Vec2, size: Vec2) -> Vec2 {
+	sign * (size + RESIZE_HANDLE_FLOATING) * 0.5
+}
+
+/// Where a handle sits relative to the center of a panel of `size`, bent along with the panel if it's curved.
+fn handle_point(sign: Vec2, size: Vec2, curve_radius: Option<f32>) -> Vec3 {
+	let offset = handle_offset(sign, size).extend(0.0);
+	match curve_radius {
+		Some(radius) => curved_panel::bend(offset, radius),
+		None => offset,
+	}
+}
+
+/// Angle around the x axis a panel with this rotation is tilted back by.
+fn pitch_of(rotation: Quat) -> f32 {
+	let normal = rotation * Vec3::Z;
+	f32::atan2(-normal.y, normal.xz().length())
+}
+
+/// What a panel's size is allowed to be while resizing.
+#[derive(Debug, Clone, Copy)]
+struct SizeLimits {
+	min: Vec2,
+	max: Vec2,
+	/// width over height to keep
+	aspect: Option<f32>,
+}
+impl SizeLimits {
+	/// `resized` says which axes the user actually changed, the others follow them to keep the aspect ratio.
+	fn constrain(&self, size: Vec2, resized: [bool; 2]) -> Vec2 {
+		let max = self.max.max(self.min);
+		let Some(aspect) = self.aspect else {
+			return size.clamp(self.min, max);
+		};
+		let height = match resized {
+			[true, true] => (size.x / aspect + size.y) * 0.5,
+			[true, false] => size.x / aspect,
+			_ => size.y,
+		}
+		.max(f32::EPSILON);
+		let size = vec2(height * aspect, height);
+		let grow = (self.min / size).max_element();
+		let shrink = (max / size).min_element();
+		if grow > 1.0 {
+			size * grow
+		} else {
+			size * shrink.min(1.0)
+		}
+	}
+}
+
+/// Size and pose of a panel held by two of its handles, `a` staying put and `b` being dragged.
+/// Along an axis neither handle spans (e.g. two opposite edge handles) the size and pitch don't change.
+fn solve_handles(
+	hmd_pos: Vec3,
+	(a, sign_a): (Vec3, Vec2),
+	(b, sign_b): (Vec3, Vec2),
+	size: Vec2,
+	pitch: f32,
+	limits: SizeLimits,
+	curve_radius: Option<f32>,
+) -> (Vec2, PanelPose) {
+	let sign_delta = sign_b - sign_a;
+	let yaw = Quat::from_rotation_y(yaw_towards(hmd_pos, a.lerp(b, 0.5))).inverse();
+	let diagonal = yaw.inverse() * (b - a);
+	let pitch = if sign_delta.y != 0.0 {
+		let diagonal = diagonal * sign_delta.y.signum();
+		f32::atan2(diagonal.z, diagonal.y)
+	} else {
+		pitch
+	};
+	let rotation = yaw * Quat::from_rotation_x(pitch);
+
+	// handle_offset(sign_b) - handle_offset(sign_a) = diagonal in panel space, solved for size
+	let mut diagonal = (rotation.inverse() * (b - a)).xy();
+	// on a curved panel the handles are a chord apart instead, so unbend it
+	if let Some(radius) = curve_radius.filter(|_| sign_delta.x != 0.0) {
+		let chord = (diagonal.x / sign_delta.x / radius).clamp(-1.0, 1.0);
+		diagonal.x = sign_delta.x * radius * chord.asin();
+	}
+	let solve_axis = |diagonal: f32, sign_delta: f32, size: f32| {
+		if sign_delta == 0.0 {
+			size
+		} else {
+			(diagonal * 2.0 / sign_delta - RESIZE_HANDLE_FLOATING).max(0.0)
+		}
+	};
+	let size = limits.constrain(
+		vec2(
+			solve_axis(diagonal.x, sign_delta.x, size.x),
+			solve_axis(diagonal.y, sign_delta.y, size.y),
+		),
+		[sign_delta.x != 0.0, sign_delta.y != 0.0],
+	);
+
+	let center = a - rotation * handle_point(sign_a, size, curve_radius);
+	(size, PanelPose::new(center, rotation))
+}
+
+/// Angle around the y axis that makes something at `point` face the HMD.
+fn yaw_towards(hmd_pos: Vec3, point: Vec3) -> f32 {
+	(point - hmd_pos).xz().to_angle() + FRAC_PI_2
+}
+
+/// Whether an input is holding down the primary button, the same way `PointerPlane` decides on left click.
+fn pressing(input: &InputData) -> bool {
+	match &input.input {
+		InputDataType::Hand(h) => {
+			Vec3::from(h.thumb.tip.position).distance(Vec3::from(h.index.tip.position)) < 0.02
+		}
+		_ => input.datamap.with_data(|d| d.idx("select").as_f32() > 0.5),
+	}
+}
+
+/// Whether a pointer is holding grip, which grabs the panel body instead of clicking into it.
+fn gripping(input: &InputData) -> bool {
+	input.datamap.with_data(|d| d.idx("grab").as_f32() > 0.90)
+}
+
+/// Whether an input is asking to scale the panel rather than resize it:
+/// a full grab with a hand, or trigger on top of grip with a controller.
+fn scale_gesture(input: &InputData) -> bool {
+	input.datamap.with_data(|d| match &input.input {
+		InputDataType::Hand(_) => d.idx("grab_strength").as_f32() > 0.90,
+		_ => d.idx("select").as_f32() > 0.5,
+	})
+}
+
+/// Smallest a panel can be scaled down to, in meters.
+const MIN_SCALED_SIZE: f32 = 0.02;
+
+/// How much of the panel, from each edge inwards, resizes instead of moves when gripped with a pointer.
+const BODY_RESIZE_MARGIN: f32 = 0.15;
+
+/// An interactive move or resize of the whole panel, either asked for by the client (e.g. by dragging
+/// a client-side title bar) or started by gripping the panel body with a pointer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClientDrag {
+	Move,
+	/// which edges follow the input, the opposite ones stay put
+	Resize {
+		up: bool,
+		down: bool,
+		left: bool,
+		right: bool,
+	},
+}
+
+struct DragInner {
+	kind: ClientDrag,
+	/// tell the client once it's done
+	client_requested: bool,
+	held: fn(&InputData) -> bool,
+	input_id: u64,
+	/// for pointers, how far along the ray the panel is held
+	pointer_distance: f32,
+	start_point: Vec3,
+	start_size: Vector2<f32>,
+	/// keep the content's pixel size and change its density instead
+	scaling: bool,
+	limits: SizeLimits,
+	start_pose: watch::Receiver<Option<PanelPose>>,
+	/// where the drag's mover should put the panel next
+	move_tx: watch::Sender<Option<MoveTarget>>,
+}
+
+/// Where a moving drag holds the panel, waiting on the hmd's position to turn it towards the user.
+#[derive(Debug, Clone, Copy)]
+struct MoveTarget {
+	grab_point: Vec3,
+	pitch: f32,
+	/// from the grab point to the panel, in the panel's space
+	offset: Vec3,
+}
+
+/// Moves the panel along with a drag, one step at a time so they land in order.
+/// Finishes the last step once the drag lets go.
+async fn move_with_drag(
+	content_parent: Spatial,
+	hmd: watch::Receiver<Option<SpatialRef>>,
+	upright_settings: UprightSettings,
+	pose_tx: watch::Sender<Option<PanelPose>>,
+	mut targets: watch::Receiver<Option<MoveTarget>>,
+) {
+	let root = content_parent.client().get_root().clone();
+	while targets.changed().await.is_ok() {
+		let Some(target) = *targets.borrow_and_update() else {
+			continue;
+		};
+		let Some(hmd) = hmd.borrow().clone() else {
+			continue;
+		};
+		let hmd_pos = pos(&hmd, &root).await;
+		let rotation = Quat::from_rotation_y(yaw_towards(hmd_pos, target.grab_point)).inverse()
+			* Quat::from_rotation_x(target.pitch);
+		let rotation = upright(&upright_settings, PanelPose::new(Vec3::ZERO, rotation)).rotation();
+		let pose = PanelPose::new(target.grab_point + rotation * target.offset, rotation);
+		let _ = content_parent.set_relative_transform(&root, pose.transform());
+		let _ = pose_tx.send(Some(pose));
+	}
+}
+
+/// New size and how far the center moves (in panel space) so that only the dragged edges follow `delta`.
+fn anchored_resize(
+	edges: [bool; 4],
+	start_size: Vec2,
+	delta: Vec2,
+	limits: SizeLimits,
+) -> (Vec2, Vec2) {
+	let [up, down, left, right] = edges;
+	// +1 when the positive edge moves, -1 for the negative one
+	let sign = vec2(
+		(right as i8 - left as i8) as f32,
+		(up as i8 - down as i8) as f32,
+	);
+	let size = limits.constrain(start_size + sign * delta, [sign.x != 0.0, sign.y != 0.0]);
+	(size, sign * (size - start_size) * 0.5)
+}
 
 async fn pos(transform: &impl SpatialRefAspect, relative_to: &impl SpatialRefAspect) -> Vec3 {
 	transform
@@ -41,6 +274,10 @@ async fn pos(transform: &impl SpatialRefAspect, relative_to: &impl SpatialRefAsp
 }
 
 pub struct ResizeHandle {
+	/// which corner or edge this is on, see `CORNER_SIGNS`
+	sign: Vec2,
+	/// stays where it is in the world instead of following the panel
+	in_world: bool,
 	settings: GrabBallSettings,
 	model: Model,
 	sphere: ModelPart,
@@ -53,6 +290,7 @@ pub struct ResizeHandle {
 impl ResizeHandle {
 	pub fn create(
 		initial_parent: &impl SpatialRefAspect,
+		sign: Vec2,
 		settings: GrabBallSettings,
 	) -> NodeResult<Self> {
 		let model = Model::create(
@@ -72,6 +310,8 @@ impl ResizeHandle {
 		let input = InputHandler::create(root, Transform::identity(), &field)?.queue()?;
 
 		Ok(ResizeHandle {
+			sign,
+			in_world: false,
 			settings,
 
 			model,
@@ -98,7 +338,10 @@ impl UIElement for ResizeHandle {
 			},
 			|input| {
 				input.datamap.with_data(|datamap| match &input.input {
-					InputDataType::Hand(_) => datamap.idx("pinch_strength").as_f32() > 0.90,
+					InputDataType::Hand(_) => {
+						datamap.idx("pinch_strength").as_f32() > 0.90
+							|| datamap.idx("grab_strength").as_f32() > 0.90
+					}
 					InputDataType::Pointer(_) => datamap.idx("grab").as_f32() > 0.90,
 					_ => datamap.idx("grab").as_f32() > 0.90,
 				})
@@ -157,13 +400,7 @@ impl ResizeHandle {
 				}
 
 				// Adjust pointer_distance based on scroll input
-				let scroll_continuous = grabbing
-					.datamap
-					.with_data(|d| d.idx("scroll_continuous").as_vector().idx(1).as_f32());
-				let scroll_discrete = grabbing
-					.datamap
-					.with_data(|d| d.idx("scroll_discrete").as_vector().idx(1).as_f32());
-				self.pointer_distance += (scroll_continuous * 0.01) + (scroll_discrete * 0.05);
+				self.pointer_distance += ScrollData::of(grabbing).push_pull();
 
 				// Calculate position at current distance along pointer ray
 				let origin = Vec3::from(p.origin);
@@ -183,13 +420,20 @@ impl ResizeHandle {
 	}
 	fn set_enabled(&mut self, enabled: bool) {
 		let _ = self.model.set_enabled(enabled);
+		let _ = self.input.handler().set_enabled(enabled);
 	}
 }
 
 pub struct ResizeHandlesInner {
 	content_parent: Spatial,
-	bottom: ResizeHandle,
-	top: ResizeHandle,
+	/// corners first, then edges
+	handles: Vec<ResizeHandle>,
+	enabled: bool,
+	edge_handles: bool,
+	resizing: bool,
+	/// the current resize keeps the content's pixel size and changes its density instead
+	scaling: bool,
+	resize_limits: SizeLimits,
 	reparentable: Option<Reparentable>,
 	reparentable_field: Field,
 	_field_update_task: AbortOnDrop,
@@ -201,8 +445,45 @@ pub struct ResizeHandlesInner {
 	is_reparentable: bool,
 	size_tx: watch::Sender<Vector2<f32>>,
 	size: watch::Receiver<Vector2<f32>>,
+	scale_tx: watch::Sender<Vector2<f32>>,
+	scale: watch::Receiver<Vector2<f32>>,
+	pose_tx: watch::Sender<Option<PanelPose>>,
+	pose: watch::Receiver<Option<PanelPose>>,
+	drag_input: InputQueue,
+	body_input: InputQueue,
+	body_grab: SingleAction,
+	drag: Option<DragInner>,
+	/// moving the panel along with the current or last drag
+	drag_mover: Option<JoinHandle<()>>,
+	client_drag_finished: bool,
+	/// where the panel would snap to if let go of now
+	snap: Option<Snap>,
+	guide_lines: Lines,
+	hmd_pos_tx: watch::Sender<Vec3>,
+	hmd_pos: watch::Receiver<Vec3>,
+	was_grabbing: bool,
+	/// where the panel was just let go of
+	released: Option<PanelPose>,
+	/// gliding towards an animated pose request
+	pose_animation: Option<AbortOnDrop>,
+	/// asked for while the panel was held, to be carried out once it's let go
+	pending_request: Option<PoseRequest>,
+	grabbing_tx: watch::Sender<bool>,
+	/// keeping up with the user when not world-locked
+	follow_task: Option<AbortOnDrop>,
+	/// where the panel sits relative to what it's anchored to, whenever it's put somewhere new
+	anchor_offset_tx: watch::Sender<Option<PanelPose>>,
+	anchor_offset: watch::Receiver<Option<PanelPose>>,
+	accent_color: Color,
 	pub min_size: Option<Vector2<f32>>,
 	pub max_size: Option<Vector2<f32>>,
+	pub aspect_lock: bool,
+	/// handles scale the panel unless told otherwise
+	pub scale_mode: bool,
+	/// how far the panel may lean while it's held
+	pub upright: UprightSettings,
+	/// the radius the panel is bent to, if it's curved
+	curve_radius: watch::Sender<Option<f32>>,
 }
 impl ResizeHandlesInner {
 	#[allow(clippy::too_many_arguments)]
@@ -224,11 +505,24 @@ impl ResizeHandlesInner {
 		};
 
 		let content_parent = Spatial::create(&parent, Transform::identity())?;
-		let bottom = ResizeHandle::create(&content_parent, settings.clone())?;
-		let top = ResizeHandle::create(&content_parent, settings.clone())?;
+		let handles = CORNER_SIGNS
+			.into_iter()
+			.chain(EDGE_SIGNS)
+			.map(|sign| ResizeHandle::create(&content_parent, sign.into(), settings.clone()))
+			.collect::<NodeResult<Vec<_>>>()?;
 
 		let (size_tx, size) = watch::channel(initial_size);
+		let (scale_tx, scale) = watch::channel(initial_size);
+		let (pose_tx, pose) = watch::channel(None);
 		let (hmd_tx, hmd_rx) = watch::channel(None);
+		let (hmd_pos_tx, hmd_pos) = watch::channel(Vec3::ZERO);
+		let (anchor_offset_tx, anchor_offset) = watch::channel(None);
+		let curve_radius = watch::Sender::new(None);
+		let guide_lines = Lines::create(
+			content_parent.client().get_root(),
+			Transform::identity(),
+			&[],
+		)?;
 		tokio::task::spawn({
 			let client = content_parent.client().clone();
 			async move {
@@ -240,16 +534,27 @@ impl ResizeHandlesInner {
 		let reparentable_field = Field::create(
 			&content_parent,
 			Transform::none(),
-			Shape::Box([initial_size.x, initial_size.y, 0.01].into()),
+			curved_panel::field_shape(initial_size.into(), 0.01, None),
 		)?;
+		let drag_input = InputHandler::create(
+			content_parent.client().get_root(),
+			Transform::identity(),
+			&reparentable_field,
+		)?
+		.queue()?;
+		let body_input =
+			InputHandler::create(&content_parent, Transform::identity(), &reparentable_field)?
+				.queue()?;
 		let _field_update_task = AbortOnDrop(
 			tokio::task::spawn({
 				let mut size = size.clone();
+				let curve_radius = curve_radius.subscribe();
 				let field = reparentable_field.clone();
 				async move {
 					while size.changed().await.is_ok() {
-						let size = size.borrow();
-						_ = field.set_shape(Shape::Box([size.x, size.y, 0.01].into()));
+						let size = Vec2::from(*size.borrow());
+						let shape = curved_panel::field_shape(size, 0.01, *curve_radius.borrow());
+						_ = field.set_shape(shape);
 					}
 				}
 			})
@@ -257,8 +562,16 @@ impl ResizeHandlesInner {
 		);
 		let mut resize_handles = ResizeHandlesInner {
 			content_parent,
-			bottom,
-			top,
+			handles,
+			enabled: true,
+			edge_handles: false,
+			resizing: false,
+			scaling: false,
+			resize_limits: SizeLimits {
+				min: Vec2::ZERO,
+				max: Vec2::splat(4096.0),
+				aspect: None,
+			},
 			parent,
 			reparentable: None,
 			reparentable_field,
@@ -270,42 +583,155 @@ impl ResizeHandlesInner {
 			is_reparentable: zoneable,
 			size_tx,
 			size,
+			scale_tx,
+			scale,
+			pose_tx,
+			pose,
+			drag_input,
+			body_input,
+			body_grab: SingleAction::default(),
+			drag: None,
+			drag_mover: None,
+			client_drag_finished: false,
+			snap: None,
+			guide_lines,
+			hmd_pos_tx,
+			hmd_pos,
+			was_grabbing: false,
+			released: None,
+			pose_animation: None,
+			pending_request: None,
+			grabbing_tx: watch::channel(false).0,
+			follow_task: None,
+			anchor_offset_tx,
+			anchor_offset,
+			accent_color,
 			min_size,
 			max_size,
+			aspect_lock: false,
+			scale_mode: false,
+			upright: UprightSettings::default(),
+			curve_radius,
 		};
 		resize_handles.set_handle_positions(initial_size);
+		resize_handles.set_enabled(true);
 		resize_handles.make_reparentable();
 		Ok(resize_handles)
 	}
 	pub fn handle_events(&mut self) {
-		let client = self.content_parent.client().clone();
-		let root = client.get_root();
-		self.bottom.handle_events();
-		self.top.handle_events();
-		if (self.top.grab_action.actor_started() && !self.bottom.grab_action.actor_acting())
-			|| (self.bottom.grab_action.actor_started() && !self.top.grab_action.actor_acting())
-		{
-			let _ = self.top.model.set_spatial_parent_in_place(root);
-			let _ = self.bottom.model.set_spatial_parent_in_place(root);
+		for handle in &mut self.handles {
+			handle.handle_events();
+		}
+		let grabbed = (0..self.handles.len())
+			.filter(|i| self.handles[*i].grab_action.actor_acting())
+			.collect::<Vec<_>>();
+		// one handle resizes against the opposite one, two handles place the panel between them
+		let pair = match grabbed[..] {
+			[] => None,
+			[b] => {
+				let sign = -self.handles[b].sign;
+				let a = self
+					.handles
+					.iter()
+					.position(|h| h.sign == sign)
+					.unwrap_or(b);
+				Some((a, b))
+			}
+			[a, b, ..] => Some((a, b)),
+		};
+
+		if pair.is_some() && !self.resizing {
+			self.resizing = true;
+			self.scaling = self.scale_mode
+				|| grabbed.iter().any(|i| {
+					self.handles[*i]
+						.grab_action
+						.actor()
+						.is_some_and(|actor| scale_gesture(actor))
+				});
+			self.resize_limits = self.size_limits(*self.size.borrow(), self.scaling);
 			let _ = self.reparentable.take();
 		}
-		if self.top.grab_action.actor_acting() || self.bottom.grab_action.actor_acting() {
-			self.update_content_transform();
+		// the handles being held or anchored to stay put while the rest follow the panel
+		let client = self.content_parent.client().clone();
+		for (i, handle) in self.handles.iter_mut().enumerate() {
+			let in_world = pair.is_some_and(|(a, b)| i == a || i == b);
+			if in_world == handle.in_world {
+				continue;
+			}
+			handle.in_world = in_world;
+			let _ = if in_world {
+				handle.model.set_spatial_parent_in_place(client.get_root())
+			} else {
+				handle
+					.model
+					.set_spatial_parent_in_place(&self.content_parent)
+			};
 		}
-
-		if (self.top.grab_action.actor_stopped() && !self.bottom.grab_action.actor_acting())
-			|| (self.bottom.grab_action.actor_stopped() && !self.top.grab_action.actor_acting())
-		{
-			let _ = self
-				.top
-				.model
-				.set_spatial_parent_in_place(&self.content_parent);
-			let _ = self
-				.bottom
-				.model
-				.set_spatial_parent_in_place(&self.content_parent);
+		if let Some(pair) = pair {
+			self.update_content_transform(pair);
+		} else if self.resizing {
+			self.resizing = false;
+			let size = *self.size.borrow();
+			self.set_handle_positions(size);
 			self.make_reparentable();
 		}
+
+		self.drag_input.handle_events();
+		self.handle_body_grab();
+		self.update_drag();
+	}
+	/// Gripping the panel body with a pointer moves it, or resizes it when gripped near an edge.
+	fn handle_body_grab(&mut self) {
+		self.body_input.handle_events();
+		self.body_grab.update(
+			true,
+			&self.body_input,
+			|input| matches!(input.input, InputDataType::Pointer(_)),
+			gripping,
+		);
+		if !self.body_grab.actor_started() || self.grabbing() {
+			return;
+		}
+		let Some(actor) = self.body_grab.actor().cloned() else {
+			return;
+		};
+		let InputDataType::Pointer(pointer) = &actor.input else {
+			return;
+		};
+		// the pointer's hit point in panel space, centered on the panel and unrolled if it's curved
+		let point = Vec3::from(pointer.deepest_point);
+		let point = match *self.curve_radius.borrow() {
+			Some(radius) => curved_panel::flatten(point, radius).xy(),
+			None => point.xy(),
+		};
+		let half_size = Vec2::from(*self.size.borrow()) * 0.5;
+		let margin = half_size * 2.0 * BODY_RESIZE_MARGIN;
+		let up = point.y > half_size.y - margin.y;
+		let down = point.y < -half_size.y + margin.y;
+		let left = point.x < -half_size.x + margin.x;
+		let right = point.x > half_size.x - margin.x;
+		let kind = if up || down || left || right {
+			ClientDrag::Resize {
+				up,
+				down,
+				left,
+				right,
+			}
+		} else {
+			ClientDrag::Move
+		};
+
+		let Some(input) = self
+			.drag_input
+			.input()
+			.keys()
+			.find(|input| input.id == actor.id)
+			.cloned()
+		else {
+			return;
+		};
+		self.start_drag(kind, &input, gripping, false);
 	}
 	fn make_reparentable(&mut self) {
 		self.reparentable = self
@@ -322,15 +748,19 @@ impl ResizeHandlesInner {
 			})
 			.flatten();
 	}
-	fn update_content_transform(&self) {
+	fn update_content_transform(&self, (a, b): (usize, usize)) {
 		let client = self.content_parent.client().clone();
 		let content_parent = self.content_parent.clone();
-		let corner1 = self.bottom.model.clone();
-		let corner2 = self.top.model.clone();
+		let (handle_a, sign_a) = (self.handles[a].model.clone(), self.handles[a].sign);
+		let (handle_b, sign_b) = (self.handles[b].model.clone(), self.handles[b].sign);
 
 		let size_tx = self.size_tx.clone();
-		let min_size = self.min_size.unwrap_or([0.0; 2].into());
-		let max_size = self.max_size.unwrap_or([4096.0; 2].into());
+		let scale_tx = self.scaling.then(|| self.scale_tx.clone());
+		let pose_tx = self.pose_tx.clone();
+		let size = Vec2::from(*self.size.borrow());
+		let limits = self.resize_limits;
+		let upright_settings = self.upright;
+		let curve_radius = *self.curve_radius.borrow();
 
 		let hmd = self.hmd.clone();
 
@@ -339,53 +769,399 @@ impl ResizeHandlesInner {
 				return;
 			};
 			let root = client.get_root();
-			let (hmd_pos, mut corner1, mut corner2) =
-				tokio::join!(pos(&hmd, root), pos(&corner1, root), pos(&corner2, root));
-			let center_point = (corner1 + corner2) * 0.5;
-
-			let center_hmd_relative = center_point - hmd_pos;
-			let y_angle = center_hmd_relative.xz().to_angle() + FRAC_PI_2;
-			let y_rotation = Quat::from_rotation_y(y_angle).inverse();
-
-			let y_aligner = Mat4::from_translation(hmd_pos).inverse()
-				* Mat4::from_rotation_y(y_angle)
-				* Mat4::from_translation(hmd_pos);
-			corner1 = y_aligner.transform_point3(corner1);
-			corner2 = y_aligner.transform_point3(corner2);
-
-			let corner1_2d = corner1.zy();
-			let corner2_2d = corner2.zy();
-			let x_angle = (corner1_2d - corner2_2d).to_angle() + FRAC_PI_2;
-			let x_rotation = Quat::from_rotation_x(x_angle).inverse();
-
-			let mut size = vec2(
-				(corner1.x - corner2.x).abs() - (RESIZE_HANDLE_FLOATING * 2.0),
-				corner1_2d.distance(corner2_2d) - (RESIZE_HANDLE_FLOATING * 2.0),
+			let (hmd_pos, a, b, transform) = tokio::join!(
+				pos(&hmd, root),
+				pos(&handle_a, root),
+				pos(&handle_b, root),
+				content_parent.get_transform(root)
 			);
-			size.x = size.x.max(min_size.x).min(max_size.x);
-			size.y = size.y.max(min_size.y).min(max_size.y);
+			let pitch = transform
+				.ok()
+				.and_then(|t| t.rotation)
+				.map(|r| pitch_of(r.into()))
+				.unwrap_or_default();
 
-			let _ = content_parent.set_relative_transform(
-				root,
-				Transform::from_translation_rotation(center_point, y_rotation * x_rotation),
+			let (size, pose) = solve_handles(
+				hmd_pos,
+				(a, sign_a),
+				(b, sign_b),
+				size,
+				pitch,
+				limits,
+				curve_radius,
 			);
+			let pose = upright(&upright_settings, pose);
+			let _ = content_parent.set_relative_transform(root, pose.transform());
 			let _ = size_tx.send(size.into());
+			if let Some(scale_tx) = scale_tx {
+				let _ = scale_tx.send(size.into());
+			}
+			let _ = pose_tx.send(Some(pose));
 		});
 	}
+	/// Limits for resizing (or scaling) a panel that starts out at `size`.
+	fn size_limits(&self, size: Vector2<f32>, scaling: bool) -> SizeLimits {
+		let ratio = (size.y > 0.0).then(|| size.x / size.y);
+		if scaling {
+			// the app's limits are in pixels, which don't change when scaling
+			return SizeLimits {
+				min: Vec2::splat(MIN_SCALED_SIZE),
+				max: Vec2::splat(4096.0),
+				aspect: ratio,
+			};
+		}
+		SizeLimits {
+			min: self.min_size.unwrap_or([0.0; 2].into()).into(),
+			max: self.max_size.unwrap_or([4096.0; 2].into()).into(),
+			aspect: ratio.filter(|_| self.aspect_lock),
+		}
+	}
 	pub fn set_handle_positions(&mut self, panel_size: Vector2<f32>) {
-		let offset = vec3(
-			panel_size.x + RESIZE_HANDLE_FLOATING,
-			panel_size.y + RESIZE_HANDLE_FLOATING,
-			0.0,
-		) * 0.5;
-		if !self.top.grab_action.actor_acting() && !self.bottom.grab_action.actor_acting() {
-			self.top.set_pos(&self.content_parent, offset);
-			self.bottom.set_pos(&self.content_parent, -offset);
+		let curve_radius = *self.curve_radius.borrow();
+		for handle in &self.handles {
+			if !handle.in_world {
+				let point = handle_point(handle.sign, panel_size.into(), curve_radius);
+				handle.set_pos(&self.content_parent, point);
+			}
 		}
 	}
+	/// Bend the handles and grabbable body along with a curved panel, or straighten them back out.
+	pub fn set_curve_radius(&mut self, curve_radius: Option<f32>) {
+		self.curve_radius.send_replace(curve_radius);
+		let size = *self.size.borrow();
+		self.set_handle_positions(size);
+		let _ = self.reparentable_field.set_shape(curved_panel::field_shape(
+			size.into(),
+			0.01,
+			curve_radius,
+		));
+	}
 	pub fn set_enabled(&mut self, enabled: bool) {
-		self.top.set_enabled(enabled);
-		self.bottom.set_enabled(enabled);
+		self.enabled = enabled;
+		for (i, handle) in self.handles.iter_mut().enumerate() {
+			handle.set_enabled(enabled && (i < CORNER_SIGNS.len() || self.edge_handles));
+		}
+	}
+	pub fn set_edge_handles(&mut self, edge_handles: bool) {
+		self.edge_handles = edge_handles;
+		self.set_enabled(self.enabled);
+	}
+	/// Hand the panel to whatever input is pressing on it, until it lets go.
+	pub fn start_client_drag(&mut self, drag: ClientDrag) {
+		if self.grabbing() {
+			return;
+		}
+		let Some(input) = self
+			.drag_input
+			.input()
+			.keys()
+			.filter(|input| pressing(input))
+			.min_by(|a, b| a.distance.total_cmp(&b.distance))
+			.cloned()
+		else {
+			self.client_drag_finished = true;
+			return;
+		};
+		self.start_drag(drag, &input, pressing, true);
+	}
+	fn start_drag(
+		&mut self,
+		kind: ClientDrag,
+		input: &InputData,
+		held: fn(&InputData) -> bool,
+		client_requested: bool,
+	) {
+		// the client asked for its pixel size to change, so never scale then
+		let scaling = !client_requested && (self.scale_mode || scale_gesture(input));
+		let pointer_distance = match &input.input {
+			InputDataType::Pointer(p) => Vec3::from(p.origin).distance(Vec3::from(p.deepest_point)),
+			_ => 0.0,
+		};
+		let start_point = Self::drag_point(input, pointer_distance);
+
+		let (start_pose_tx, start_pose) = watch::channel(None);
+		tokio::task::spawn({
+			let client = self.content_parent.client().clone();
+			let content_parent = self.content_parent.clone();
+			async move {
+				let Ok(transform) = content_parent.get_transform(client.get_root()).await else {
+					return;
+				};
+				let _ = start_pose_tx.send(PanelPose::from_transform(&transform));
+			}
+		});
+
+		let (move_tx, move_rx) = watch::channel(None);
+		let mover = tokio::task::spawn(move_with_drag(
+			self.content_parent.clone(),
+			self.hmd.clone(),
+			self.upright,
+			self.pose_tx.clone(),
+			move_rx,
+		));
+		if let Some(old) = self.drag_mover.replace(mover) {
+			old.abort();
+		}
+
+		let _ = self.reparentable.take();
+		self.drag.replace(DragInner {
+			kind,
+			client_requested,
+			held,
+			input_id: input.id,
+			pointer_distance,
+			start_point,
+			start_size: *self.size.borrow(),
+			scaling,
+			limits: self.size_limits(*self.size.borrow(), scaling),
+			start_pose,
+			move_tx,
+		});
+	}
+	/// The point an input is dragging, relative to the client root.
+	fn drag_point(input: &InputData, pointer_distance: f32) -> Vec3 {
+		match &input.input {
+			InputDataType::Pointer(p) => {
+				Vec3::from(p.origin) + Vec3::from(p.direction()).normalize() * pointer_distance
+			}
+			InputDataType::Hand(h) => {
+				Vec3::from(h.thumb.tip.position).lerp(Vec3::from(h.index.tip.position), 0.5)
+			}
+			InputDataType::Tip(t) => t.origin.into(),
+		}
+	}
+	fn update_drag(&mut self) {
+		let Some(drag) = &mut self.drag else {
+			return;
+		};
+		let Some(input) = self
+			.drag_input
+			.input()
+			.keys()
+			.find(|input| input.id == drag.input_id)
+			.filter(|input| (drag.held)(input))
+			.cloned()
+		else {
+			if self.drag.take().is_some_and(|drag| drag.client_requested) {
+				self.client_drag_finished = true;
+			}
+			self.make_reparentable();
+			return;
+		};
+		let Some(start_pose) = *drag.start_pose.borrow() else {
+			return;
+		};
+		if let ClientDrag::Resize {
+			up,
+			down,
+			left,
+			right,
+		} = drag.kind
+		{
+			let rotation = start_pose.rotation();
+			let curve_radius = *self.curve_radius.borrow();
+			// in the panel's space, unrolled if it's curved
+			let to_local = |point: Vec3| {
+				let point = rotation.inverse() * (point - start_pose.translation());
+				match curve_radius {
+					Some(radius) => curved_panel::flatten(point, radius),
+					None => point,
+				}
+			};
+			let point = match &input.input {
+				// follow where the ray crosses the panel's surface, like the pointer cursor does
+				InputDataType::Pointer(p) => {
+					let origin =
+						rotation.inverse() * (Vec3::from(p.origin) - start_pose.translation());
+					let direction = rotation.inverse() * Vec3::from(p.direction());
+					let hit = match curve_radius {
+						Some(radius) => curved_panel::ray_hit(origin, direction, radius)
+							.map(|hit| curved_panel::flatten(hit, radius)),
+						None => (direction.z.abs() > f32::EPSILON)
+							.then(|| origin - direction * (origin.z / direction.z)),
+					};
+					hit.unwrap_or_else(|| to_local(Self::drag_point(&input, drag.pointer_distance)))
+				}
+				_ => to_local(Self::drag_point(&input, drag.pointer_distance)),
+			};
+			let delta = (point - to_local(drag.start_point)).xy();
+			let (size, center_offset) = anchored_resize(
+				[up, down, left, right],
+				drag.start_size.into(),
+				delta,
+				drag.limits,
+			);
+			let scaling = drag.scaling;
+			let pose = PanelPose::new(
+				start_pose.translation() + rotation * center_offset.extend(0.0),
+				rotation,
+			);
+			let root = self.content_parent.client().get_root();
+			let _ = self
+				.content_parent
+				.set_relative_transform(root, pose.transform());
+			if *self.size.borrow() != Vector2::from(size) {
+				let _ = self.size_tx.send(size.into());
+				if scaling {
+					let _ = self.scale_tx.send(size.into());
+				}
+			}
+			let _ = self.pose_tx.send(Some(pose));
+			return;
+		}
+
+		let rotation = start_pose.rotation();
+		let pitch = pitch_of(rotation);
+		let offset = rotation.inverse() * (start_pose.translation() - drag.start_point);
+		if let InputDataType::Pointer(_) = &input.input {
+			// push and pull along the ray with scroll, like the handles
+			drag.pointer_distance += ScrollData::of(&input).push_pull();
+		}
+		let _ = drag.move_tx.send(Some(MoveTarget {
+			grab_point: Self::drag_point(&input, drag.pointer_distance),
+			pitch,
+			offset,
+		}));
+	}
+	/// Move the panel somewhere else, or once it's let go of if someone is holding onto it.
+	pub fn request_pose(&mut self, request: PoseRequest) {
+		self.pose_animation.take();
+		if self.grabbing() {
+			self.pending_request.replace(request);
+			return;
+		}
+		let client = self.content_parent.client().clone();
+		let content_parent = self.content_parent.clone();
+		let pose_tx = self.pose_tx.clone();
+		let hmd = self.hmd.clone();
+		let start = *self.pose.borrow();
+		let drag_mover = self.drag_mover.take();
+
+		let task = tokio::task::spawn(async move {
+			if let Some(drag_mover) = drag_mover {
+				let _ = drag_mover.await;
+			}
+			let root = client.get_root();
+			let pose = match request.anchor {
+				PoseAnchor::Stage => request.pose,
+				PoseAnchor::Body => {
+					let Some(hmd) = hmd.borrow().clone() else {
+						return;
+					};
+					let Ok(hmd_transform) = hmd.get_transform(root).await else {
+						return;
+					};
+					let (body_translation, body_rotation) = body_frame(&hmd_transform);
+					PanelPose::new(
+						body_translation + body_rotation * request.pose.translation(),
+						body_rotation * request.pose.rotation(),
+					)
+				}
+			};
+			match start.filter(|_| request.animated) {
+				Some(start) => {
+					glide(&content_parent, root, start, pose, |step| {
+						let _ = pose_tx.send(Some(step));
+					})
+					.await
+				}
+				None => {
+					let _ = content_parent.set_relative_transform(root, pose.transform());
+					let _ = pose_tx.send(Some(pose));
+				}
+			}
+		});
+		self.pose_animation
+			.replace(AbortOnDrop(task.abort_handle()));
+	}
+	pub fn grabbing(&self) -> bool {
+		self.resizing || self.drag.is_some()
+	}
+	/// Start or stop keeping up with the user.
+	pub fn set_anchor(&mut self, anchor: Anchor) {
+		self.follow_task = (anchor != Anchor::World).then(|| {
+			AbortOnDrop(
+				tokio::task::spawn(follow(
+					anchor,
+					self.content_parent.clone(),
+					self.hmd.clone(),
+					self.grabbing_tx.subscribe(),
+					self.pose.clone(),
+					self.pose_tx.clone(),
+					self.anchor_offset_tx.clone(),
+				))
+				.abort_handle(),
+			)
+		});
+	}
+	/// Show where the panel would snap to while it's held, and put it there once it's let go.
+	pub fn update_snap(&mut self, settings: &SnapSettings, targets: &[SnapTarget]) {
+		let grabbing = self.grabbing();
+		if grabbing {
+			self.update_hmd_pos();
+			let size = Vec2::from(*self.size.borrow());
+			let hmd_pos = *self.hmd_pos.borrow();
+			self.snap = (*self.pose.borrow())
+				.and_then(|pose| snap(settings, &self.upright, pose, size, hmd_pos, targets));
+			self.draw_guides(size);
+		} else if self.was_grabbing {
+			self.released = *self.pose.borrow();
+			let _ = self.guide_lines.set_lines(&[]);
+			// whatever asked for the panel to move while it was held wins over snapping
+			if let Some(request) = self.pending_request.take() {
+				self.snap.take();
+				self.request_pose(request);
+			} else if let Some(snap) = self.snap.take() {
+				let client = self.content_parent.client().clone();
+				let content_parent = self.content_parent.clone();
+				let pose_tx = self.pose_tx.clone();
+				let drag_mover = self.drag_mover.take();
+				tokio::task::spawn(async move {
+					// the drag's last step has to land first or it would undo the snap
+					if let Some(drag_mover) = drag_mover {
+						let _ = drag_mover.await;
+					}
+					let _ = content_parent
+						.set_relative_transform(client.get_root(), snap.pose.transform());
+					let _ = pose_tx.send(Some(snap.pose));
+				});
+			}
+		}
+		self.was_grabbing = grabbing;
+	}
+	fn update_hmd_pos(&self) {
+		let client = self.content_parent.client().clone();
+		let hmd = self.hmd.clone();
+		let hmd_pos_tx = self.hmd_pos_tx.clone();
+		tokio::task::spawn(async move {
+			let Some(hmd) = hmd.borrow().clone() else {
+				return;
+			};
+			let _ = hmd_pos_tx.send(pos(&hmd, client.get_root()).await);
+		});
+	}
+	/// An outline of where the panel would land, plus the edges it would line up with.
+	fn draw_guides(&self, size: Vec2) {
+		let Some(snap) = &self.snap else {
+			let _ = self.guide_lines.set_lines(&[]);
+			return;
+		};
+		let outline = rounded_rectangle(size.x, size.y, 0.01, 4)
+			.thickness(0.002)
+			.color(self.accent_color)
+			.transform(Mat4::from_rotation_translation(
+				snap.pose.rotation(),
+				snap.pose.translation(),
+			));
+		let lines = [outline]
+			.into_iter()
+			.chain(snap.guides.iter().map(|[start, end]| {
+				line_from_points(vec![*start, *end])
+					.thickness(0.004)
+					.color(self.accent_color)
+			}))
+			.collect::<Vec<_>>();
+		let _ = self.guide_lines.set_lines(&lines);
 	}
 }
 
@@ -395,10 +1171,44 @@ impl ResizeHandlesInner {
 #[allow(clippy::type_complexity)]
 pub struct ResizeHandles<State: ValidState> {
 	pub reparentable: bool,
+	/// whether the handles can be seen and grabbed
+	pub enabled: bool,
+	/// also put handles on the middle of each edge, not just the corners
+	pub edge_handles: bool,
+	pub pose_request: Option<PoseRequest>,
+	pub client_drag: Option<ClientDrag>,
 	pub current_size: Vector2<f32>,
 	pub min_size: Option<Vector2<f32>>,
+	/// infinite along an axis without a limit
 	pub max_size: Option<Vector2<f32>>,
+	/// keep the width to height ratio when resizing
+	pub aspect_lock: bool,
+	/// grabbing the handles scales the panel, like the scale gesture does
+	pub scale_mode: bool,
+	/// the radius the panel is bent to, if it's curved, see `curved_panel`
+	#[setters(strip_option = false)]
+	pub curve_radius: Option<f32>,
 	pub on_size_changed: FnWrapper<dyn Fn(&mut State, Vector2<f32>) + Send + Sync>,
+	/// called with the new size in meters when the panel is scaled instead of resized,
+	/// the content should keep its size in pixels
+	pub on_scaled: FnWrapper<dyn Fn(&mut State, Vector2<f32>) + Send + Sync>,
+	/// called whenever the panel moves, including while it's being held or following its anchor
+	pub on_pose_changed: FnWrapper<dyn Fn(&mut State, PanelPose) + Send + Sync>,
+	/// called with where the panel sits relative to its anchor whenever it's put somewhere new,
+	/// but not while it's just following the anchor around
+	pub on_anchor_offset_changed: FnWrapper<dyn Fn(&mut State, PanelPose) + Send + Sync>,
+	/// where the panel settles when let go of
+	pub snap: SnapSettings,
+	/// other panels to line up with
+	pub snap_targets: Vec<SnapTarget>,
+	/// how far the panel may lean
+	pub upright: UprightSettings,
+	/// what the panel stays put relative to
+	pub anchor: Anchor,
+	/// called once the input carrying out `client_drag` lets go
+	pub on_client_drag_finished: FnWrapper<dyn Fn(&mut State) + Send + Sync>,
+	/// called with where the panel was let go of, before it snaps anywhere
+	pub on_released: FnWrapper<dyn Fn(&mut State, PanelPose) + Send + Sync>,
 }
 impl<State: ValidState> CustomElement<State> for ResizeHandles<State> {
 	type Inner = ResizeHandlesInner;
@@ -411,7 +1221,7 @@ impl<State: ValidState> CustomElement<State> for ResizeHandles<State> {
 		info: CreateInnerInfo,
 		_resource: &mut Self::Resource,
 	) -> Result<Self::Inner, Self::Error> {
-		ResizeHandlesInner::create(
+		let mut inner = ResizeHandlesInner::create(
 			info.parent_space.clone(),
 			context.dbus_connection.clone(),
 			info.element_path,
@@ -420,15 +1230,56 @@ impl<State: ValidState> CustomElement<State> for ResizeHandles<State> {
 			self.current_size,
 			self.min_size,
 			self.max_size,
-		)
+		)?;
+		inner.aspect_lock = self.aspect_lock;
+		inner.scale_mode = self.scale_mode;
+		inner.upright = self.upright;
+		inner.set_curve_radius(self.curve_radius);
+		inner.set_edge_handles(self.edge_handles);
+		inner.set_enabled(self.enabled);
+		inner.set_anchor(self.anchor);
+		if let Some(request) = self.pose_request {
+			inner.request_pose(request);
+		}
+		Ok(inner)
 	}
 
 	fn diff(&self, old: &Self, inner: &mut Self::Inner, _resource: &mut Self::Resource) {
 		inner.min_size = self.min_size;
 		inner.max_size = self.max_size;
+		inner.aspect_lock = self.aspect_lock;
+		inner.scale_mode = self.scale_mode;
+		inner.upright = self.upright;
 		if self.current_size != old.current_size {
+			// keep track of size changes from elsewhere without reporting them back
+			if *inner.size.borrow() != self.current_size {
+				inner.size_tx.send_replace(self.current_size);
+				inner.size.borrow_and_update();
+			}
 			inner.set_handle_positions(self.current_size);
 		}
+		if self.curve_radius != old.curve_radius {
+			inner.set_curve_radius(self.curve_radius);
+		}
+		if self.enabled != old.enabled {
+			inner.set_enabled(self.enabled);
+		}
+		if self.edge_handles != old.edge_handles {
+			inner.set_edge_handles(self.edge_handles);
+		}
+		if self.anchor != old.anchor {
+			inner.set_anchor(self.anchor);
+		}
+		if self.pose_request != old.pose_request {
+			if let Some(request) = self.pose_request {
+				inner.request_pose(request);
+			}
+		}
+		if self.client_drag != old.client_drag {
+			if let Some(drag) = self.client_drag {
+				inner.start_client_drag(drag);
+			}
+		}
 	}
 
 	fn frame(
@@ -439,10 +1290,39 @@ impl<State: ValidState> CustomElement<State> for ResizeHandles<State> {
 		inner: &mut Self::Inner,
 	) {
 		inner.handle_events();
+		let grabbing = inner.grabbing();
+		if grabbing {
+			// whoever grabbed it gets the last word
+			inner.pose_animation.take();
+		}
+		inner
+			.grabbing_tx
+			.send_if_modified(|g| std::mem::replace(g, grabbing) != grabbing);
+		inner.update_snap(&self.snap, &self.snap_targets);
+		if inner.client_drag_finished {
+			inner.client_drag_finished = false;
+			(self.on_client_drag_finished.0)(state);
+		}
+		if let Some(pose) = inner.released.take() {
+			(self.on_released.0)(state, pose);
+		}
 
-		if inner.size.has_changed().is_ok_and(|t| t) {
+		if inner.scale.has_changed().is_ok_and(|t| t) {
+			inner.size.borrow_and_update();
+			(self.on_scaled.0)(state, *inner.scale.borrow_and_update());
+		} else if inner.size.has_changed().is_ok_and(|t| t) {
 			(self.on_size_changed.0)(state, *inner.size.borrow_and_update());
 		}
+		if inner.pose.has_changed().is_ok_and(|t| t) {
+			if let Some(pose) = *inner.pose.borrow_and_update() {
+				(self.on_pose_changed.0)(state, pose);
+			}
+		}
+		if inner.anchor_offset.has_changed().is_ok_and(|t| t) {
+			if let Some(offset) = *inner.anchor_offset.borrow_and_update() {
+				(self.on_anchor_offset_changed.0)(state, offset);
+			}
+		}
 	}
 
 	fn spatial_aspect(&self, inner: &Self::Inner) -> SpatialRef {
@@ -476,19 +1356,41 @@ async fn test_resize_handles() {
 		const APP_ID: &'static str = "org.stardustxr.flatland.ResizeHandles";
 	}
 	impl Reify for State {
-		fn reify(&self) -> impl stardust_xr_asteroids::Element<Self> {
+		fn reify(
+			&self,
+			_context: &Context,
+			_tasks: impl stardust_xr_asteroids::Tasker<Self>,
+		) -> impl stardust_xr_asteroids::Element<Self> {
 			stardust_xr_asteroids::elements::Spatial::default()
 				.rot(Quat::from_rotation_y(self.time / 10.0))
 				.build()
 				.child(
 					ResizeHandles::<Self> {
 						reparentable: true,
+						enabled: true,
+						edge_handles: true,
+						pose_request: None,
+						client_drag: None,
 						current_size: self.size,
 						min_size: None,
 						max_size: None,
+						aspect_lock: false,
+						scale_mode: false,
+						curve_radius: None,
+						snap: SnapSettings::default(),
+						snap_targets: Vec::new(),
+						upright: UprightSettings::default(),
+						anchor: Anchor::World,
 						on_size_changed: FnWrapper(Box::new(|state, new_size| {
 							state.size = new_size;
 						})),
+						on_scaled: FnWrapper(Box::new(|state, new_size| {
+							state.size = new_size;
+						})),
+						on_pose_changed: FnWrapper(Box::new(|_, _| {})),
+						on_anchor_offset_changed: FnWrapper(Box::new(|_, _| {})),
+						on_client_drag_finished: FnWrapper(Box::new(|_| {})),
+						on_released: FnWrapper(Box::new(|_, _| {})),
 					}
 					.build()
 					.child(
@@ -502,3 +1404,100 @@ async fn test_resize_handles() {
 
 	client::run::<State>(&[]).await;
 }
+
+#[cfg(test)]
+const UNLIMITED: SizeLimits = SizeLimits {
+	min: Vec2::ZERO,
+	max: Vec2::splat(4096.0),
+	aspect: None,
+};
+
+#[test]
+fn corner_resize_keeps_opposite_corner() {
+	let size = vec2(0.4, 0.3);
+	let anchor = Vec3::new(-0.2, 0.8, -1.0);
+	let (new_size, pose) = solve_handles(
+		Vec3::new(0.0, 1.0, 0.0),
+		(anchor, Vec2::new(-1.0, -1.0)),
+		(Vec3::new(0.5, 1.3, -1.0), Vec2::new(1.0, 1.0)),
+		size,
+		0.0,
+		UNLIMITED,
+		None,
+	);
+	let corner = pose.translation()
+		+ pose.rotation() * handle_offset(Vec2::new(-1.0, -1.0), new_size).extend(0.0);
+	assert!(corner.distance(anchor) < 0.0001);
+	assert!(new_size.x > size.x && new_size.y > size.y);
+}
+
+#[test]
+fn curved_corner_resize_unbends_width() {
+	let size = vec2(0.6, 0.3);
+	let radius = Some(0.5);
+	let anchor = Vec3::new(-0.35, 0.8, -1.0);
+	let (new_size, pose) = solve_handles(
+		Vec3::new(0.0, 1.0, 0.0),
+		(anchor, Vec2::new(-1.0, -1.0)),
+		(Vec3::new(0.35, 1.3, -1.0), Vec2::new(1.0, 1.0)),
+		size,
+		0.0,
+		UNLIMITED,
+		radius,
+	);
+	let corner = pose.translation()
+		+ pose.rotation() * handle_point(Vec2::new(-1.0, -1.0), new_size, radius);
+	assert!(corner.distance(anchor) < 0.0001);
+	// the handles are a chord apart, the panel is as wide as the arc between them
+	assert!(new_size.x + RESIZE_HANDLE_FLOATING > 0.75);
+}
+
+#[test]
+fn edge_resize_keeps_height() {
+	let size = vec2(0.4, 0.3);
+	let (new_size, _) = solve_handles(
+		Vec3::ZERO,
+		(Vec3::new(-0.2, 0.0, -1.0), Vec2::new(-1.0, 0.0)),
+		(Vec3::new(0.4, 0.1, -1.0), Vec2::new(1.0, 0.0)),
+		size,
+		0.0,
+		UNLIMITED,
+		None,
+	);
+	assert_eq!(new_size.y, size.y);
+	assert!(new_size.x > size.x);
+}
+
+#[test]
+fn aspect_lock_keeps_ratio() {
+	let limits = SizeLimits {
+		aspect: Some(16.0 / 9.0),
+		..UNLIMITED
+	};
+	let size = limits.constrain(vec2(1.0, 0.2), [true, true]);
+	assert!((size.x / size.y - 16.0 / 9.0).abs() < 0.0001);
+	let size = limits.constrain(vec2(0.8, 0.2), [true, false]);
+	assert!((size.y - 0.45).abs() < 0.0001);
+}
+
+#[test]
+fn unlimited_axis_doesnt_shrink() {
+	// what an app limiting only its height ends up as
+	let limits = SizeLimits {
+		min: Vec2::ZERO,
+		max: vec2(f32::INFINITY, 0.5),
+		aspect: None,
+	};
+	assert_eq!(
+		limits.constrain(vec2(3.0, 1.0), [true, true]),
+		vec2(3.0, 0.5)
+	);
+	let limits = SizeLimits {
+		aspect: Some(2.0),
+		..limits
+	};
+	assert_eq!(
+		limits.constrain(vec2(0.8, 0.4), [true, true]),
+		vec2(0.8, 0.4)
+	);
+}
@@ -0,0 +1,337 @@
+use crate::panel_pose::PanelPose;
+use glam::{vec2, vec3, Quat, Vec2, Vec3, Vec3Swizzles};
+use serde::{Deserialize, Serialize};
+
+/// Gap left between panels snapped next to each other, in meters.
+const NEIGHBOR_GAP: f32 = 0.01;
+/// How far in front of or behind a neighbor a panel can be and still line up with it, in meters.
+const COPLANAR_DISTANCE: f32 = 0.15;
+/// How much a panel can be turned away from a neighbor and still line up with it, in radians.
+const COPLANAR_ANGLE: f32 = 0.35;
+
+/// Where panels settle when let go of.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapSettings {
+	/// line edges up with nearby panels
+	pub neighbors: bool,
+	/// how close edges have to be to line up, in meters
+	pub neighbor_distance: f32,
+	/// round the direction and distance from the user to a grid
+	pub grid: bool,
+	/// in degrees
+	pub grid_angle: f32,
+	/// in meters
+	pub grid_distance: f32,
+	/// take out any sideways tilt
+	pub level: bool,
+}
+impl Default for SnapSettings {
+	fn default() -> Self {
+		SnapSettings {
+			neighbors: false,
+			neighbor_distance: 0.05,
+			grid: false,
+			grid_angle: 5.0,
+			grid_distance: 0.25,
+			level: false,
+		}
+	}
+}
+
+/// How far panels are allowed to lean.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Upright {
+	/// any way at all
+	Free,
+	/// straight up and down, only turning left and right
+	YawOnly,
+	/// no sideways tilt, and only tipped back or forward up to `max_pitch`
+	BoundedPitch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UprightSettings {
+	pub mode: Upright,
+	/// in degrees
+	pub max_pitch: f32,
+}
+impl Default for UprightSettings {
+	fn default() -> Self {
+		UprightSettings {
+			mode: Upright::Free,
+			max_pitch: 30.0,
+		}
+	}
+}
+
+/// Another panel to line up with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnapTarget {
+	pub pose: PanelPose,
+	/// in meters
+	pub size: Vec2,
+}
+
+/// Where a panel would snap to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snap {
+	pub pose: PanelPose,
+	/// the edges it lines up with, relative to the client root
+	pub guides: Vec<[Vec3; 2]>,
+}
+
+/// Where a panel of `size` let go of at `pose` should end up, if it should move at all.
+pub fn snap(
+	settings: &SnapSettings,
+	upright_settings: &UprightSettings,
+	pose: PanelPose,
+	size: Vec2,
+	hmd_pos: Vec3,
+	neighbors: &[SnapTarget],
+) -> Option<Snap> {
+	let mut snapped = pose;
+	if settings.level {
+		snapped = level(snapped);
+	}
+	if settings.grid {
+		snapped = snap_to_grid(settings, snapped, hmd_pos);
+	}
+	let mut guides = Vec::new();
+	if settings.neighbors {
+		// a neighbor decides the panel's plane, so it wins over the grid
+		if let Some((neighbor_snap, edges)) = neighbors
+			.iter()
+			.filter_map(|neighbor| snap_to_neighbor(settings, pose, size, neighbor))
+			.min_by(|(a, _), (b, _)| {
+				let distance = |p: &PanelPose| p.translation().distance(pose.translation());
+				distance(a).total_cmp(&distance(b))
+			}) {
+			snapped = neighbor_snap;
+			guides = edges;
+		}
+	}
+	snapped = upright(upright_settings, snapped);
+
+	let moved = snapped.translation().distance(pose.translation()) > 0.0005
+		|| snapped.rotation().angle_between(pose.rotation()) > 0.005;
+	(moved || !guides.is_empty()).then_some(Snap {
+		pose: snapped,
+		guides,
+	})
+}
+
+/// Which way a rotation turns a panel and how far it tips it back, leaving out any roll.
+fn yaw_pitch(rotation: Quat) -> (f32, f32) {
+	let normal = rotation * Vec3::Z;
+	(
+		normal.x.atan2(normal.z),
+		(-normal.y).atan2(normal.xz().length()),
+	)
+}
+
+/// Keep the way the panel faces but take out any roll.
+pub fn level(pose: PanelPose) -> PanelPose {
+	let (yaw, pitch) = yaw_pitch(pose.rotation());
+	PanelPose::new(
+		pose.translation(),
+		Quat::from_rotation_y(yaw) * Quat::from_rotation_x(pitch),
+	)
+}
+
+/// Take out as much lean as the settings ask for, turning the panel around its center.
+pub fn upright(settings: &UprightSettings, pose: PanelPose) -> PanelPose {
+	let max_pitch = match settings.mode {
+		Upright::Free => return pose,
+		Upright::YawOnly => 0.0,
+		Upright::BoundedPitch => settings.max_pitch.to_radians().abs(),
+	};
+	let (yaw, pitch) = yaw_pitch(pose.rotation());
+	PanelPose::new(
+		pose.translation(),
+		Quat::from_rotation_y(yaw) * Quat::from_rotation_x(pitch.clamp(-max_pitch, max_pitch)),
+	)
+}
+
+/// Round the panel's direction and distance from the user, then face the user.
+fn snap_to_grid(settings: &SnapSettings, pose: PanelPose, hmd_pos: Vec3) -> PanelPose {
+	let offset = pose.translation() - hmd_pos;
+	let distance = offset.length();
+	if distance <= f32::EPSILON {
+		return pose;
+	}
+	let step = settings.grid_angle.max(0.1).to_radians();
+	let round = |value: f32, step: f32| (value / step).round() * step;
+	let azimuth = round(offset.x.atan2(-offset.z), step);
+	let elevation = round((offset.y / distance).clamp(-1.0, 1.0).asin(), step);
+	let distance = round(distance, settings.grid_distance.max(0.01)).max(settings.grid_distance);
+
+	let rotation = Quat::from_rotation_y(-azimuth) * Quat::from_rotation_x(elevation);
+	PanelPose::new(hmd_pos + rotation * Vec3::NEG_Z * distance, rotation)
+}
+
+/// Line the panel's edges up with a neighbor that's about in the same plane.
+fn snap_to_neighbor(
+	settings: &SnapSettings,
+	pose: PanelPose,
+	size: Vec2,
+	neighbor: &SnapTarget,
+) -> Option<(PanelPose, Vec<[Vec3; 2]>)> {
+	let local = neighbor.pose.inverse().compose(&pose);
+	let center = local.translation();
+	let facing = local.rotation() * Vec3::Z;
+	if center.z.abs() > COPLANAR_DISTANCE || facing.angle_between(Vec3::Z) > COPLANAR_ANGLE {
+		return None;
+	}
+	let half = size * 0.5;
+	let other = neighbor.size * 0.5;
+	let overlap = (center.xy().abs() - (half + other)).cmplt(Vec2::ZERO);
+
+	// (offset, where the shared edge is) for the closest candidate along one axis
+	let closest = |current: f32, candidates: &[(f32, f32)]| {
+		candidates
+			.iter()
+			.map(|(target, edge)| (target - current, *edge))
+			.filter(|(offset, _)| offset.abs() < settings.neighbor_distance)
+			.min_by(|(a, _), (b, _)| a.abs().total_cmp(&b.abs()))
+	};
+	// beside the neighbor, or lined up with its left or right edge when above or below it
+	let x = closest(
+		center.x,
+		&[
+			overlap
+				.y
+				.then_some((other.x + NEIGHBOR_GAP + half.x, other.x)),
+			overlap
+				.y
+				.then_some((-(other.x + NEIGHBOR_GAP + half.x), -other.x)),
+			overlap.x.then_some((half.x - other.x, -other.x)),
+			overlap.x.then_some((other.x - half.x, other.x)),
+		]
+		.into_iter()
+		.flatten()
+		.collect::<Vec<_>>(),
+	);
+	let y = closest(
+		center.y,
+		&[
+			overlap
+				.x
+				.then_some((other.y + NEIGHBOR_GAP + half.y, other.y)),
+			overlap
+				.x
+				.then_some((-(other.y + NEIGHBOR_GAP + half.y), -other.y)),
+			overlap.y.then_some((other.y - half.y, other.y)),
+			overlap.y.then_some((half.y - other.y, -other.y)),
+		]
+		.into_iter()
+		.flatten()
+		.collect::<Vec<_>>(),
+	);
+	if x.is_none() && y.is_none() {
+		return None;
+	}
+
+	let center = vec2(
+		center.x + x.map(|(offset, _)| offset).unwrap_or_default(),
+		center.y + y.map(|(offset, _)| offset).unwrap_or_default(),
+	);
+	let to_world = |point: Vec3| {
+		neighbor
+			.pose
+			.compose(&PanelPose::new(point, Quat::IDENTITY))
+			.translation()
+	};
+	// vertical guides span both panels' heights, horizontal ones both widths
+	let mut guides = Vec::new();
+	if let Some((_, edge)) = x {
+		let bottom = (center.y - half.y).min(-other.y);
+		let top = (center.y + half.y).max(other.y);
+		guides.push([
+			to_world(vec3(edge, bottom, 0.0)),
+			to_world(vec3(edge, top, 0.0)),
+		]);
+	}
+	if let Some((_, edge)) = y {
+		let left = (center.x - half.x).min(-other.x);
+		let right = (center.x + half.x).max(other.x);
+		guides.push([
+			to_world(vec3(left, edge, 0.0)),
+			to_world(vec3(right, edge, 0.0)),
+		]);
+	}
+	Some((
+		neighbor
+			.pose
+			.compose(&PanelPose::new(center.extend(0.0), Quat::IDENTITY)),
+		guides,
+	))
+}
+
+#[test]
+fn snaps_beside_neighbor() {
+	let neighbor = SnapTarget {
+		pose: PanelPose::new(vec3(0.0, 1.0, -1.0), Quat::IDENTITY),
+		size: vec2(0.4, 0.3),
+	};
+	let pose = PanelPose::new(vec3(0.43, 1.02, -1.05), Quat::from_rotation_z(0.05));
+	let settings = SnapSettings {
+		neighbors: true,
+		level: true,
+		..Default::default()
+	};
+	let snap = snap(
+		&settings,
+		&UprightSettings::default(),
+		pose,
+		vec2(0.4, 0.3),
+		Vec3::ZERO,
+		&[neighbor],
+	)
+	.unwrap();
+	assert!(
+		snap.pose
+			.translation()
+			.distance(vec3(0.4 + NEIGHBOR_GAP, 1.0, -1.0))
+			< 0.0001
+	);
+	assert!(snap.pose.rotation().angle_between(Quat::IDENTITY) < 0.0001);
+	assert_eq!(snap.guides.len(), 2);
+}
+
+#[test]
+fn grid_faces_user() {
+	let settings = SnapSettings {
+		grid: true,
+		neighbors: false,
+		..Default::default()
+	};
+	let pose = PanelPose::new(vec3(0.02, 0.01, -1.1), Quat::IDENTITY);
+	let snap = snap(
+		&settings,
+		&UprightSettings::default(),
+		pose,
+		vec2(0.4, 0.3),
+		Vec3::ZERO,
+		&[],
+	)
+	.unwrap();
+	assert!(snap.pose.translation().distance(vec3(0.0, 0.0, -1.0)) < 0.0001);
+}
+
+#[test]
+fn upright_bounds_pitch() {
+	let settings = UprightSettings {
+		mode: Upright::BoundedPitch,
+		max_pitch: 20.0,
+	};
+	let tilted =
+		Quat::from_rotation_y(0.5) * Quat::from_rotation_x(0.8) * Quat::from_rotation_z(0.3);
+	let pose = upright(&settings, PanelPose::new(Vec3::ZERO, tilted));
+	let (yaw, pitch) = yaw_pitch(pose.rotation());
+	assert!((pitch - 20.0_f32.to_radians()).abs() < 0.0001);
+	assert!((yaw - yaw_pitch(tilted).0).abs() < 0.0001);
+	// no roll left, so the panel's x axis stays level
+	assert!((pose.rotation() * Vec3::X).y.abs() < 0.0001);
+}
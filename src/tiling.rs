@@ -0,0 +1,166 @@
+use crate::panel_pose::PanelPose;
+use glam::{vec2, Quat, Vec2, Vec3};
+use serde::{Deserialize, Serialize};
+
+/// How tiled panels split up the space around the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TilingLayout {
+	/// the first panel takes a big share on the left, the rest stack up on the right
+	MasterStack,
+	/// rows and columns of the same size
+	Grid,
+	/// side by side, each the full height
+	Columns,
+}
+
+/// What tiled panels are arranged on, centered on the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TilingSurface {
+	/// panels are stacked straight up and down
+	Cylinder,
+	/// panels above and below eye level tilt to face the user
+	Sphere,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TilingSettings {
+	pub enabled: bool,
+	pub layout: TilingLayout,
+	pub surface: TilingSurface,
+	/// distance from the user, in meters
+	pub radius: f32,
+	/// how far around the user the tiles go, in degrees
+	pub arc: f32,
+	/// in meters, measured along the surface
+	pub height: f32,
+	/// between tiles, in meters
+	pub gap: f32,
+	/// how much of the width the master panel gets
+	pub master_ratio: f32,
+}
+impl Default for TilingSettings {
+	fn default() -> Self {
+		TilingSettings {
+			enabled: false,
+			layout: TilingLayout::MasterStack,
+			surface: TilingSurface::Cylinder,
+			radius: 1.2,
+			arc: 150.0,
+			height: 0.9,
+			gap: 0.03,
+			master_ratio: 0.6,
+		}
+	}
+}
+
+/// Where a tiled panel goes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Slot {
+	/// relative to the user's body, see `PoseAnchor::Body`
+	pub pose: PanelPose,
+	/// in meters
+	pub size: Vec2,
+}
+
+/// Slots for `count` panels, in tiling order.
+pub fn slots(settings: &TilingSettings, count: usize) -> Vec<Slot> {
+	let radius = settings.radius.max(0.1);
+	let area = vec2(radius * settings.arc.to_radians(), settings.height);
+	rects(settings, count, area)
+		.into_iter()
+		.map(|(center, size)| Slot {
+			pose: place(settings.surface, radius, center),
+			size: (size - settings.gap).max(Vec2::splat(0.05)),
+		})
+		.collect()
+}
+
+/// Centers and sizes of the tiles on the surface unrolled flat, centered on the middle of `area`.
+fn rects(settings: &TilingSettings, count: usize, area: Vec2) -> Vec<(Vec2, Vec2)> {
+	// from the top left corner of the area, +y down
+	let cell = |x: f32, y: f32, width: f32, height: f32| {
+		(
+			vec2(
+				x + width / 2.0 - area.x / 2.0,
+				area.y / 2.0 - y - height / 2.0,
+			),
+			vec2(width, height),
+		)
+	};
+	match (settings.layout, count) {
+		(_, 0) => Vec::new(),
+		(TilingLayout::MasterStack, 1) => vec![cell(0.0, 0.0, area.x, area.y)],
+		(TilingLayout::MasterStack, _) => {
+			let master_width = area.x * settings.master_ratio.clamp(0.1, 0.9);
+			let stack_height = area.y / (count - 1) as f32;
+			std::iter::once(cell(0.0, 0.0, master_width, area.y))
+				.chain((0..count - 1).map(|i| {
+					cell(
+						master_width,
+						i as f32 * stack_height,
+						area.x - master_width,
+						stack_height,
+					)
+				}))
+				.collect()
+		}
+		(TilingLayout::Grid, _) => {
+			let columns = (count as f32).sqrt().ceil() as usize;
+			let rows = count.div_ceil(columns);
+			let size = vec2(area.x / columns as f32, area.y / rows as f32);
+			(0..count)
+				.map(|i| {
+					let (column, row) = (i % columns, i / columns);
+					cell(column as f32 * size.x, row as f32 * size.y, size.x, size.y)
+				})
+				.collect()
+		}
+		(TilingLayout::Columns, _) => {
+			let width = area.x / count as f32;
+			(0..count)
+				.map(|i| cell(i as f32 * width, 0.0, width, area.y))
+				.collect()
+		}
+	}
+}
+
+/// Pose of a tile centered at `center` on the unrolled surface, facing the user.
+fn place(surface: TilingSurface, radius: f32, center: Vec2) -> PanelPose {
+	let yaw = Quat::from_rotation_y(-center.x / radius);
+	match surface {
+		TilingSurface::Cylinder => {
+			PanelPose::new(yaw * Vec3::NEG_Z * radius + Vec3::Y * center.y, yaw)
+		}
+		TilingSurface::Sphere => {
+			let rotation = yaw * Quat::from_rotation_x(center.y / radius);
+			PanelPose::new(rotation * Vec3::NEG_Z * radius, rotation)
+		}
+	}
+}
+
+#[test]
+fn master_takes_its_share() {
+	let settings = TilingSettings {
+		gap: 0.0,
+		..Default::default()
+	};
+	let slots = slots(&settings, 3);
+	let width = settings.radius * settings.arc.to_radians();
+	assert!((slots[0].size.x - width * settings.master_ratio).abs() < 0.0001);
+	assert!((slots[1].size.y - settings.height / 2.0).abs() < 0.0001);
+	assert!(slots[1].pose.translation().y > slots[2].pose.translation().y);
+}
+
+#[test]
+fn grid_fills_rows_first() {
+	let settings = TilingSettings {
+		layout: TilingLayout::Grid,
+		..Default::default()
+	};
+	let slots = slots(&settings, 5);
+	assert_eq!(slots.len(), 5);
+	// 3 columns, so the fourth starts the second row under the first
+	assert!((slots[0].pose.translation().x - slots[3].pose.translation().x).abs() < 0.0001);
+	assert!(slots[0].pose.translation().y > slots[3].pose.translation().y);
+}
@@ -0,0 +1,279 @@
+use glam::Vec2;
+
+/// How far a finger can move and still tap, in meters.
+const TAP_SLOP: f32 = 0.01;
+/// How long a finger has to stay put to right click, in seconds.
+const LONG_PRESS_TIME: f32 = 0.5;
+/// How far two fingers have to move together before they scroll, in meters.
+const SCROLL_THRESHOLD: f32 = 0.01;
+/// How far two fingers have to spread apart or pinch together before they zoom, in meters.
+const ZOOM_THRESHOLD: f32 = 0.015;
+/// How much one ctrl+wheel click zooms most apps, like browsers.
+const ZOOM_STEP: f32 = 1.1;
+
+/// Pointer input made up from touches, for apps that don't support touch themselves.
+/// Positions are in meters from the top left corner of the plane, +y down.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TouchGesture {
+	/// move the cursor here
+	Motion(Vec2),
+	Button {
+		button: u32,
+		pressed: bool,
+	},
+	/// how far two fingers dragged the content
+	Scroll(Vec2),
+	/// two fingers stopped scrolling
+	ScrollStop,
+	/// how many times further apart two fingers are than last time
+	Zoom(f32),
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+enum Gesture {
+	#[default]
+	Idle,
+	/// one finger down, not yet moved or held long enough to be anything else than a tap
+	Pending {
+		id: u32,
+		start: Vec2,
+		since: f32,
+	},
+	/// one finger dragging with the left button down
+	Dragging {
+		id: u32,
+	},
+	Scrolling {
+		center: Vec2,
+	},
+	Zooming {
+		spread: f32,
+	},
+	/// two fingers down, not yet moved enough to scroll or zoom
+	TwoFingers {
+		center: Vec2,
+		spread: f32,
+	},
+	/// waiting for every finger to lift
+	Done,
+}
+
+/// Tells taps, drags, long presses (right click), two finger scrolling and pinching apart.
+#[derive(Debug, Default)]
+pub struct TouchGestures {
+	gesture: Gesture,
+}
+impl TouchGestures {
+	/// `touches` are every finger touching the plane this frame, `elapsed` is in seconds.
+	pub fn update(&mut self, touches: &[(u32, Vec2)], elapsed: f32) -> Vec<TouchGesture> {
+		let left = input_event_codes::BTN_LEFT!();
+		let right = input_event_codes::BTN_RIGHT!();
+		let click = |pos: Vec2, button: u32| {
+			vec![
+				TouchGesture::Motion(pos),
+				TouchGesture::Button {
+					button,
+					pressed: true,
+				},
+				TouchGesture::Button {
+					button,
+					pressed: false,
+				},
+			]
+		};
+		let two_fingers = match touches {
+			[(_, a), (_, b), ..] => Some(((*a + *b) / 2.0, a.distance(*b))),
+			_ => None,
+		};
+		let finger = |id: u32| touches.iter().find(|(i, _)| *i == id).map(|(_, pos)| *pos);
+
+		let mut gestures = Vec::new();
+		self.gesture = match (self.gesture, two_fingers) {
+			(Gesture::Idle | Gesture::Pending { .. }, Some((center, spread))) => {
+				Gesture::TwoFingers { center, spread }
+			}
+			(Gesture::Idle, None) => match touches.first() {
+				Some((id, start)) => Gesture::Pending {
+					id: *id,
+					start: *start,
+					since: elapsed,
+				},
+				None => Gesture::Idle,
+			},
+			(Gesture::Pending { id, start, since }, None) => match finger(id) {
+				None => {
+					gestures = click(start, left);
+					Gesture::Idle
+				}
+				Some(pos) if pos.distance(start) > TAP_SLOP => {
+					gestures = vec![
+						TouchGesture::Motion(start),
+						TouchGesture::Button {
+							button: left,
+							pressed: true,
+						},
+						TouchGesture::Motion(pos),
+					];
+					Gesture::Dragging { id }
+				}
+				Some(_) if elapsed - since > LONG_PRESS_TIME => {
+					gestures = click(start, right);
+					Gesture::Done
+				}
+				Some(_) => Gesture::Pending { id, start, since },
+			},
+			(Gesture::Dragging { id }, _) => match finger(id) {
+				Some(pos) => {
+					gestures.push(TouchGesture::Motion(pos));
+					Gesture::Dragging { id }
+				}
+				None => {
+					gestures.push(TouchGesture::Button {
+						button: left,
+						pressed: false,
+					});
+					Gesture::Done
+				}
+			},
+			(Gesture::TwoFingers { center, spread }, Some((new_center, new_spread))) => {
+				if (new_spread - spread).abs() > ZOOM_THRESHOLD {
+					Gesture::Zooming { spread: new_spread }
+				} else if new_center.distance(center) > SCROLL_THRESHOLD {
+					Gesture::Scrolling { center: new_center }
+				} else {
+					Gesture::TwoFingers { center, spread }
+				}
+			}
+			(Gesture::Scrolling { center }, Some((new_center, _))) => {
+				gestures.push(TouchGesture::Scroll(new_center - center));
+				Gesture::Scrolling { center: new_center }
+			}
+			(Gesture::Scrolling { .. }, None) => {
+				gestures.push(TouchGesture::ScrollStop);
+				Gesture::Done
+			}
+			(Gesture::Zooming { spread }, Some((_, new_spread))) => {
+				if spread > f32::EPSILON {
+					gestures.push(TouchGesture::Zoom(new_spread / spread));
+				}
+				Gesture::Zooming { spread: new_spread }
+			}
+			(Gesture::TwoFingers { .. } | Gesture::Zooming { .. }, None) => Gesture::Done,
+			(Gesture::Done, _) => Gesture::Done,
+		};
+		if touches.is_empty() && matches!(self.gesture, Gesture::Done) {
+			self.gesture = Gesture::Idle;
+		}
+		gestures
+	}
+
+	/// Let go of whatever's held down, then ignore the fingers already down until they've all lifted.
+	pub fn cancel(&mut self) -> Vec<TouchGesture> {
+		let gestures = match self.gesture {
+			Gesture::Dragging { .. } => vec![TouchGesture::Button {
+				button: input_event_codes::BTN_LEFT!(),
+				pressed: false,
+			}],
+			Gesture::Scrolling { .. } => vec![TouchGesture::ScrollStop],
+			_ => Vec::new(),
+		};
+		self.gesture = Gesture::Done;
+		gestures
+	}
+}
+
+/// Turns zooming by a factor into ctrl+wheel clicks, keeping the leftover for next time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ZoomSteps(f32);
+impl Default for ZoomSteps {
+	fn default() -> Self {
+		ZoomSteps(1.0)
+	}
+}
+impl ZoomSteps {
+	/// How many clicks to zoom in by after zooming by `factor`, negative to zoom out.
+	pub fn add(&mut self, factor: f32) -> i32 {
+		self.0 *= factor;
+		let steps = (self.0.ln() / ZOOM_STEP.ln()).trunc();
+		self.0 /= ZOOM_STEP.powf(steps);
+		steps as i32
+	}
+}
+
+#[test]
+fn tap_and_long_press_click() {
+	use glam::vec2;
+	let mut gestures = TouchGestures::default();
+	gestures.update(&[(0, vec2(0.1, 0.1))], 0.0);
+	gestures.update(&[(0, vec2(0.105, 0.1))], 0.1);
+	let tap = gestures.update(&[], 0.2);
+	assert_eq!(tap[0], TouchGesture::Motion(vec2(0.1, 0.1)));
+	assert_eq!(
+		tap[1],
+		TouchGesture::Button {
+			button: input_event_codes::BTN_LEFT!(),
+			pressed: true
+		}
+	);
+
+	gestures.update(&[(1, vec2(0.1, 0.1))], 1.0);
+	let long_press = gestures.update(&[(1, vec2(0.1, 0.1))], 1.6);
+	assert_eq!(
+		long_press[1],
+		TouchGesture::Button {
+			button: input_event_codes::BTN_RIGHT!(),
+			pressed: true
+		}
+	);
+	assert!(gestures.update(&[], 1.7).is_empty());
+}
+
+#[test]
+fn two_fingers_scroll_or_zoom() {
+	use glam::vec2;
+	let mut gestures = TouchGestures::default();
+	gestures.update(&[(0, vec2(0.1, 0.1)), (1, vec2(0.15, 0.1))], 0.0);
+	gestures.update(&[(0, vec2(0.1, 0.12)), (1, vec2(0.15, 0.12))], 0.1);
+	let scroll = gestures.update(&[(0, vec2(0.1, 0.13)), (1, vec2(0.15, 0.13))], 0.2);
+	assert_eq!(scroll.len(), 1);
+	let TouchGesture::Scroll(moved) = scroll[0] else {
+		panic!("not scrolling");
+	};
+	assert!((moved.y - 0.01).abs() < 0.0001);
+	assert_eq!(gestures.update(&[], 0.3), vec![TouchGesture::ScrollStop]);
+
+	gestures.update(&[(0, vec2(0.1, 0.1)), (1, vec2(0.15, 0.1))], 1.0);
+	gestures.update(&[(0, vec2(0.08, 0.1)), (1, vec2(0.17, 0.1))], 1.1);
+	let zoom = gestures.update(&[(0, vec2(0.07, 0.1)), (1, vec2(0.18, 0.1))], 1.2);
+	let TouchGesture::Zoom(factor) = zoom[0] else {
+		panic!("not zooming");
+	};
+	assert!((factor - 0.11 / 0.09).abs() < 0.0001);
+}
+
+#[test]
+fn cancel_lets_go_of_a_drag() {
+	use glam::vec2;
+	let mut gestures = TouchGestures::default();
+	gestures.update(&[(0, vec2(0.1, 0.1))], 0.0);
+	gestures.update(&[(0, vec2(0.2, 0.1))], 0.1);
+	assert_eq!(
+		gestures.cancel(),
+		vec![TouchGesture::Button {
+			button: input_event_codes::BTN_LEFT!(),
+			pressed: false
+		}]
+	);
+	// the finger still down doesn't start anything new
+	assert!(gestures.update(&[(0, vec2(0.3, 0.1))], 0.2).is_empty());
+	assert!(gestures.update(&[], 0.3).is_empty());
+}
+
+#[test]
+fn zoom_steps_carry_over() {
+	let mut steps = ZoomSteps::default();
+	assert_eq!(steps.add(1.05), 0);
+	assert_eq!(steps.add(1.05), 1);
+	assert_eq!(steps.add(ZOOM_STEP.powi(3)), 3);
+	assert_eq!(steps.add(1.0 / ZOOM_STEP.powi(5)), -4);
+}
@@ -1,12 +1,12 @@
 use derive_setters::Setters;
-use glam::{vec3, Mat4, Vec2, Vec3};
+use glam::{vec2, vec3, Mat4, Vec2, Vec3};
 use rustc_hash::FxHashMap;
 use stardust_xr_asteroids::{
 	Context, CreateInnerInfo, CustomElement, FnWrapper, Transformable, ValidState,
 };
 use stardust_xr_fusion::{
 	drawable::{Line, LinePoint, Lines, LinesAspect},
-	fields::{Field, FieldAspect, Shape},
+	fields::{Field, FieldAspect},
 	input::{InputData, InputDataType, InputHandler},
 	node::{NodeError, NodeType},
 	root::FrameInfo,
@@ -21,6 +21,11 @@ use stardust_xr_molecules::{
 };
 use std::time::Duration;
 
+use crate::{
+	curved_panel::{self, field_shape},
+	touch_gestures::{TouchGesture, TouchGestures},
+};
+
 #[derive_where::derive_where(Debug, PartialEq)]
 #[derive(Setters)]
 #[setters(into, strip_option)]
@@ -31,6 +36,11 @@ pub struct TouchPlane<State: ValidState> {
 	pub thickness: f32,
 	pub click_freeze_time: Duration,
 	pub debug_line_settings: Option<DebugSettings>,
+	/// bend the plane around a vertical cylinder of this radius, see `curved_panel`
+	#[setters(strip_option = false)]
+	pub curve_radius: Option<f32>,
+	/// send touches to `on_gesture` as made up mouse input instead of as raw touches
+	pub gestures: bool,
 
 	#[setters(skip)]
 	pub on_touch_down: FnWrapper<dyn Fn(&mut State, u32, Vector3<f32>) + Send + Sync>,
@@ -38,6 +48,8 @@ pub struct TouchPlane<State: ValidState> {
 	pub on_touch_move: FnWrapper<dyn Fn(&mut State, u32, Vector3<f32>) + Send + Sync>,
 	#[setters(skip)]
 	pub on_touch_up: FnWrapper<dyn Fn(&mut State, u32) + Send + Sync>,
+	#[setters(skip)]
+	pub on_gesture: FnWrapper<dyn Fn(&mut State, TouchGesture) + Send + Sync>,
 }
 
 impl<State: ValidState> Default for TouchPlane<State> {
@@ -48,10 +60,13 @@ impl<State: ValidState> Default for TouchPlane<State> {
 			thickness: 0.0,
 			click_freeze_time: Duration::from_millis(300),
 			debug_line_settings: None,
+			curve_radius: None,
+			gestures: false,
 
 			on_touch_down: FnWrapper(Box::new(|_, _, _| {})),
 			on_touch_move: FnWrapper(Box::new(|_, _, _| {})),
 			on_touch_up: FnWrapper(Box::new(|_, _| {})),
+			on_gesture: FnWrapper(Box::new(|_, _| {})),
 		}
 	}
 }
@@ -77,6 +92,14 @@ impl<State: ValidState> TouchPlane<State> {
 		self.on_touch_up = FnWrapper(Box::new(f));
 		self
 	}
+
+	pub fn on_gesture(
+		mut self,
+		f: impl Fn(&mut State, TouchGesture) + Send + Sync + 'static,
+	) -> Self {
+		self.on_gesture = FnWrapper(Box::new(f));
+		self
+	}
 }
 
 impl<State: ValidState> CustomElement<State> for TouchPlane<State> {
@@ -93,7 +116,7 @@ impl<State: ValidState> CustomElement<State> for TouchPlane<State> {
 		let field = Field::create(
 			info.parent_space,
 			self.transform,
-			Shape::Box([self.physical_size.x, self.physical_size.y, self.thickness].into()),
+			field_shape(self.physical_size.into(), self.thickness, self.curve_radius),
 		)?;
 
 		let input = InputHandler::create(&field, Transform::none(), &field)?.queue()?;
@@ -104,8 +127,11 @@ impl<State: ValidState> CustomElement<State> for TouchPlane<State> {
 			field,
 			touch: MultiAction::default(),
 			start_tap_times: FxHashMap::default(),
+			gestures: TouchGestures::default(),
+			gestures_enabled: self.gestures,
 			physical_size: self.physical_size.into(),
 			thickness: self.thickness,
+			curve_radius: self.curve_radius,
 			lines,
 			debug_line_settings: self.debug_line_settings,
 		})
@@ -116,7 +142,10 @@ impl<State: ValidState> CustomElement<State> for TouchPlane<State> {
 		if self.debug_line_settings != old_self.debug_line_settings {
 			inner.set_debug(self.debug_line_settings);
 		}
-		if self.physical_size != old_self.physical_size {
+		if self.physical_size != old_self.physical_size
+			|| self.curve_radius != old_self.curve_radius
+		{
+			inner.curve_radius = self.curve_radius;
 			inner.resize(self.physical_size.into());
 		}
 	}
@@ -128,6 +157,9 @@ impl<State: ValidState> CustomElement<State> for TouchPlane<State> {
 		state: &mut State,
 		inner: &mut Self::Inner,
 	) {
+		if self.gestures != inner.gestures_enabled {
+			inner.switch_modes(state, self);
+		}
 		inner.handle_events(state, self, info);
 	}
 
@@ -150,8 +182,12 @@ pub struct TouchSurfaceInputInner {
 	field: Field,
 	touch: MultiAction,
 	start_tap_times: FxHashMap<u32, f32>,
+	gestures: TouchGestures,
+	/// whether touches were last turned into gestures, to notice it changing
+	gestures_enabled: bool,
 	physical_size: Vec2,
 	thickness: f32,
+	curve_radius: Option<f32>,
 	lines: Lines,
 	debug_line_settings: Option<DebugSettings>,
 }
@@ -170,10 +206,24 @@ impl TouchSurfaceInputInner {
 		self.update_signifiers();
 	}
 
+	/// Switch between raw touches and gestures, letting go of whatever the old mode held down.
+	/// Fingers already down are ignored until they lift.
+	fn switch_modes<State: ValidState>(&mut self, state: &mut State, decl: &TouchPlane<State>) {
+		self.gestures_enabled = decl.gestures;
+		for (id, _) in self.start_tap_times.drain() {
+			(decl.on_touch_up.0)(state, id);
+		}
+		for gesture in self.gestures.cancel() {
+			(decl.on_gesture.0)(state, gesture);
+		}
+	}
+
 	pub fn resize(&mut self, physical_size: Vec2) {
 		self.physical_size = physical_size;
-		let _ = self.field.set_shape(Shape::Box(
-			[physical_size.x, physical_size.y, self.thickness].into(),
+		let _ = self.field.set_shape(field_shape(
+			physical_size,
+			self.thickness,
+			self.curve_radius,
 		));
 	}
 
@@ -187,11 +237,24 @@ impl TouchSurfaceInputInner {
 			&& point.z.is_sign_positive() == front
 	}
 
-	fn hover_point(input: &InputData) -> Vec3 {
-		match &input.input {
+	/// Where the input is touching, in flat coordinates if the plane is curved.
+	fn hover_point(input: &InputData, curve_radius: Option<f32>) -> Vec3 {
+		let point = match &input.input {
 			InputDataType::Hand(h) => Vec3::from(h.index.tip.position),
 			InputDataType::Tip(t) => t.origin.into(),
 			_ => Vec3::ZERO,
+		};
+		match curve_radius {
+			Some(radius) => curved_panel::flatten(point, radius),
+			None => point,
+		}
+	}
+
+	/// Flat coordinates back to the field's space.
+	fn bend(&self, point: Vec3) -> Vec3 {
+		match self.curve_radius {
+			Some(radius) => curved_panel::bend(point, radius),
+			None => point,
 		}
 	}
 
@@ -211,30 +274,53 @@ impl TouchSurfaceInputInner {
 		info: &FrameInfo,
 	) {
 		let physical_size = self.physical_size.into();
+		let curve_radius = self.curve_radius;
 		self.touch.update(
 			&self.input,
 			|input| match &input.input {
 				InputDataType::Pointer(_) => false,
-				InputDataType::Hand(h) => Self::hovering(physical_size, h.index.tip.position, true),
-				InputDataType::Tip(t) => Self::hovering(physical_size, t.origin, true),
+				_ => Self::hovering(
+					physical_size,
+					Self::hover_point(input, curve_radius).into(),
+					true,
+				),
 			},
 			|input| match &input.input {
-				InputDataType::Hand(h) => {
-					Self::hovering(physical_size, h.index.tip.position, false)
-				}
-				InputDataType::Tip(t) => Self::hovering(physical_size, t.origin, false),
-				_ => false,
+				InputDataType::Pointer(_) => false,
+				_ => Self::hovering(
+					physical_size,
+					Self::hover_point(input, curve_radius).into(),
+					false,
+				),
 			},
 		);
 
+		if decl.gestures {
+			let mut touches = self
+				.touch
+				.interact()
+				.current()
+				.iter()
+				.map(|input| {
+					let position = self.to_local_coords(Self::hover_point(input, curve_radius));
+					(input.id as u32, vec2(position.x, position.y))
+				})
+				.collect::<Vec<_>>();
+			touches.sort_by_key(|(id, _)| *id);
+			for gesture in self.gestures.update(&touches, info.elapsed) {
+				(decl.on_gesture.0)(state, gesture);
+			}
+			return;
+		}
+
 		for input_data in self.touch.interact().added().iter() {
-			let position = self.to_local_coords(Self::hover_point(input_data));
+			let position = self.to_local_coords(Self::hover_point(input_data, self.curve_radius));
 			self.start_tap_times
 				.insert(input_data.id as u32, info.elapsed);
 			(decl.on_touch_down.0)(state, input_data.id as u32, position);
 		}
 		for input_data in self.touch.interact().current().iter() {
-			let position = self.to_local_coords(Self::hover_point(input_data));
+			let position = self.to_local_coords(Self::hover_point(input_data, self.curve_radius));
 			if let Some(start_time) = self.start_tap_times.get(&(input_data.id as u32)) {
 				if info.elapsed - start_time > decl.click_freeze_time.as_secs_f32() {
 					(decl.on_touch_move.0)(state, input_data.id as u32, position);
@@ -242,8 +328,14 @@ impl TouchSurfaceInputInner {
 			}
 		}
 		for input_data in self.touch.interact().removed().iter() {
-			self.start_tap_times.remove(&(input_data.id as u32));
-			(decl.on_touch_up.0)(state, input_data.id as u32);
+			// touches from before switching to raw touches never went down
+			if self
+				.start_tap_times
+				.remove(&(input_data.id as u32))
+				.is_some()
+			{
+				(decl.on_touch_up.0)(state, input_data.id as u32);
+			}
 		}
 	}
 
@@ -284,7 +376,7 @@ impl TouchSurfaceInputInner {
 	}
 
 	fn line_from_input(&self, input: &InputData) -> Line {
-		self.line_from_point(Self::hover_point(input))
+		self.line_from_point(Self::hover_point(input, self.curve_radius))
 	}
 
 	fn line_from_point(&self, point: Vec3) -> Line {
@@ -292,21 +384,22 @@ impl TouchSurfaceInputInner {
 		Line {
 			points: vec![
 				LinePoint {
-					point: [
-						point
-							.x
-							.clamp(self.physical_size.x * -0.5, self.physical_size.x * 0.5),
-						point
-							.y
-							.clamp(self.physical_size.y * -0.5, self.physical_size.y * 0.5),
-						0.0,
-					]
-					.into(),
+					point: self
+						.bend(vec3(
+							point
+								.x
+								.clamp(self.physical_size.x * -0.5, self.physical_size.x * 0.5),
+							point
+								.y
+								.clamp(self.physical_size.y * -0.5, self.physical_size.y * 0.5),
+							0.0,
+						))
+						.into(),
 					thickness: settings.line_start_thickness,
 					color: settings.line_start_color_interact,
 				},
 				LinePoint {
-					point: point.into(),
+					point: self.bend(point).into(),
 					thickness: settings.line_end_thickness,
 					color: settings.line_end_color_interact,
 				},
@@ -0,0 +1,62 @@
+use crate::{action_button::ActionButton, State, TOOLBAR_BUTTON_SIZE};
+use stardust_xr_asteroids::{
+	elements::{Spatial, Text},
+	CustomElement, Element, Transformable as _,
+};
+use stardust_xr_fusion::drawable::{TextBounds, TextFit, XAlign, YAlign};
+
+/// Minimized toplevels in the tray, in meters.
+const TRAY_ICON_SIZE: [f32; 2] = [0.1, 0.03];
+
+/// An icon for each minimized toplevel, in the order they were opened.
+/// Tapping one brings it back onto the current workspace.
+pub fn tray(state: &State) -> impl Element<State> {
+	let minimized = state
+		.tiling_order
+		.iter()
+		.filter(|uid| state.toplevels.get(uid).is_some_and(|t| t.minimized))
+		.collect::<Vec<_>>();
+	let count = minimized.len() as f32;
+	Spatial::default()
+		.pos([
+			0.0,
+			TOOLBAR_BUTTON_SIZE[1] / 2.0 + 0.01 + TRAY_ICON_SIZE[1] / 2.0,
+			0.0,
+		])
+		.build()
+		.stable_children(minimized.into_iter().enumerate().map(|(i, uid)| {
+			let uid = *uid;
+			let title = state.toplevels[&uid].title_text();
+			(
+				uid,
+				ActionButton::new(TRAY_ICON_SIZE, move |state: &mut State| {
+					let current = state.current_workspace.clone();
+					if let Some(toplevel) = state.toplevels.get_mut(&uid) {
+						toplevel.minimized = false;
+						toplevel.workspace = current;
+					}
+					state.update_visibility();
+				})
+				.pos([
+					(i as f32 - (count - 1.0) / 2.0) * (TRAY_ICON_SIZE[0] + 0.005),
+					0.0,
+					0.0,
+				])
+				.build()
+				.child(
+					Text::new(title)
+						.character_height(TRAY_ICON_SIZE[1] * 0.4)
+						.align_x(XAlign::Center)
+						.align_y(YAlign::Center)
+						.bounds(TextBounds {
+							bounds: [TRAY_ICON_SIZE[0] - 0.01, TRAY_ICON_SIZE[1]].into(),
+							fit: TextFit::Squeeze,
+							anchor_align_x: XAlign::Center,
+							anchor_align_y: YAlign::Center,
+						})
+						.pos([0.0, 0.0, 0.001])
+						.build(),
+				),
+			)
+		}))
+}
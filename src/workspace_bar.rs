@@ -0,0 +1,77 @@
+use crate::{action_button::ActionButton, State, TOOLBAR_BUTTON_SIZE};
+use stardust_xr_asteroids::{
+	elements::{Spatial, Text},
+	CustomElement, Element, Transformable as _,
+};
+use stardust_xr_fusion::drawable::{XAlign, YAlign};
+
+/// A button for each workspace, the current one lit up, and one for the overview.
+pub fn workspace_bar(state: &State) -> impl Element<State> {
+	let count = state.workspaces.len() as f32;
+	Spatial::default()
+		.build()
+		.child(
+			ActionButton::new(TOOLBAR_BUTTON_SIZE, |state: &mut State| {
+				state.overview = !state.overview;
+				if state.overview {
+					for toplevel in state.toplevels.values_mut() {
+						toplevel.highlighted = false;
+					}
+				}
+			})
+			.active(state.overview)
+			.pos([
+				(count + 1.0) / 2.0 * (TOOLBAR_BUTTON_SIZE[0] + 0.005),
+				0.0,
+				0.0,
+			])
+			.build()
+			.child(
+				Text::new("all")
+					.character_height(TOOLBAR_BUTTON_SIZE[1] * 0.5)
+					.align_x(XAlign::Center)
+					.align_y(YAlign::Center)
+					.pos([0.0, 0.0, 0.001])
+					.build(),
+			),
+		)
+		.child(
+			ActionButton::new(TOOLBAR_BUTTON_SIZE, |state: &mut State| state.summon_all())
+				.pos([
+					(count + 3.0) / 2.0 * (TOOLBAR_BUTTON_SIZE[0] + 0.005),
+					0.0,
+					0.0,
+				])
+				.build()
+				.child(
+					Text::new("here")
+						.character_height(TOOLBAR_BUTTON_SIZE[1] * 0.5)
+						.align_x(XAlign::Center)
+						.align_y(YAlign::Center)
+						.pos([0.0, 0.0, 0.001])
+						.build(),
+				),
+		)
+		.children(state.workspaces.iter().enumerate().map(|(i, name)| {
+			let workspace = name.clone();
+			ActionButton::new(TOOLBAR_BUTTON_SIZE, move |state: &mut State| {
+				state.current_workspace.clone_from(&workspace);
+				state.update_visibility();
+			})
+			.active(*name == state.current_workspace)
+			.pos([
+				(i as f32 - (count - 1.0) / 2.0) * (TOOLBAR_BUTTON_SIZE[0] + 0.005),
+				0.0,
+				0.0,
+			])
+			.build()
+			.child(
+				Text::new(name)
+					.character_height(TOOLBAR_BUTTON_SIZE[1] * 0.5)
+					.align_x(XAlign::Center)
+					.align_y(YAlign::Center)
+					.pos([0.0, 0.0, 0.001])
+					.build(),
+			)
+		}))
+}